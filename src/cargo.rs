@@ -1,31 +1,65 @@
-use std::process::Command;
+use std::path::Path;
 use rr_result::RrResult;
-use utils::check_output;
+use runner::{Cmd, CommandRunner};
 
-pub fn build_release() -> RrResult<()> {
-    let output = Command::new("cargo")
-        .arg("build")
-        .arg("--release")
-        .output()?;
-
-    check_output(&output)?;
-    Ok(())
+/// Runs the cargo commands by a `CommandRunner`, without network access if `offline`.
+pub struct Cargo<'a> {
+    runner: &'a dyn CommandRunner,
+    offline: bool
 }
 
-pub fn test() -> RrResult<()> {
-    let output = Command::new("cargo")
-        .arg("test")
-        .output()?;
+impl<'a> Cargo<'a> {
+    pub fn new(runner: &'a dyn CommandRunner, offline: bool) -> Cargo<'a> {
+        Cargo { runner, offline }
+    }
 
-    check_output(&output)?;
-    Ok(())
-}
+    /// Builds a release.
+    pub fn build_release(&self) -> RrResult<()> {
+        self.run(self.cargo("build").arg("--release"))
+    }
+
+    /// Updates the `Cargo.lock` to the current version of the cargo project.
+    ///
+    /// Resolving the dependencies by `cargo metadata` rewrites the version of the
+    /// project in the `Cargo.lock` without updating any other dependency.
+    pub fn update_lock_file(&self) -> RrResult<()> {
+        self.run(self.cargo("metadata").arg("--format-version=1"))
+    }
+
+    /// Runs the tests.
+    pub fn test(&self) -> RrResult<()> {
+        self.run(self.cargo("test"))
+    }
+
+    /// Publishes to `registry` or to crates.io if it's `None`, the cargo project
+    /// of `manifest` or if it's `None` the one of the current directory.
+    ///
+    /// Publishing always needs network access, so it ignores `offline`.
+    pub fn publish(&self, registry: Option<&str>, manifest: Option<&Path>) -> RrResult<()> {
+        let mut cmd = Cmd::new("cargo").arg("publish");
+        if let Some(manifest) = manifest {
+            cmd = cmd.arg("--manifest-path").arg(manifest);
+        }
+
+        if let Some(registry) = registry {
+            cmd = cmd.arg("--registry").arg(registry);
+        }
+
+        self.run(cmd)
+    }
 
-pub fn publish() -> RrResult<()> {
-    let output = Command::new("cargo")
-        .arg("publish")
-        .output()?;
+    /// The cargo command `subcommand`, with `--offline` if `offline`.
+    fn cargo(&self, subcommand: &str) -> Cmd {
+        let cmd = Cmd::new("cargo").arg(subcommand);
+        if self.offline {
+            cmd.arg("--offline")
+        } else {
+            cmd
+        }
+    }
 
-    check_output(&output)?;
-    Ok(())
+    fn run(&self, cmd: Cmd) -> RrResult<()> {
+        self.runner.run(&cmd)?.check()?;
+        Ok(())
+    }
 }
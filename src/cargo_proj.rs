@@ -1,10 +1,22 @@
+use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::ffi::OsStr;
 use toml;
 use semver::Version;
 use rr_result::RrResult;
-use utils::{modify_file, map_file};
+use utils::{modify_file, map_file, file_list};
+
+/// The well-known names of changelog files - relative to the root directory
+/// of the cargo project - ordered by their preference.
+const CHANGELOG_NAMES: &[&str] = &[
+    "CHANGELOG.md",
+    "CHANGELOG",
+    "CHANGES.md",
+    "HISTORY.md",
+    "NEWS",
+    "docs/CHANGELOG.md"
+];
 
 #[derive(Debug)]
 pub struct CargoProj {
@@ -17,15 +29,25 @@ pub struct CargoProj {
     /// the path to the `Cargo.toml`
     cargo_toml: PathBuf,
 
-    /// the path to an optinal changelog file
-    changelog: Option<PathBuf>
+    /// the path to an optinal changelog file, set by `find_changelog`
+    changelog: Option<PathBuf>,
+
+    /// the `[package.metadata.rusty-release]` table of the `Cargo.toml`
+    metadata: Option<toml::Value>,
+
+    /// the path to the root `Cargo.toml` of the workspace containing the
+    /// cargo project, which is `cargo_toml` if it isn't a workspace member
+    workspace_toml: PathBuf,
+
+    /// the `[workspace.metadata.rusty-release]` table of the `workspace_toml`
+    workspace_metadata: Option<toml::Value>
 }
 
 impl CargoProj {
     /// Searches for the root directory (containing a `Cargo.toml`) of the cargo project starting
     /// at `start_dir` and continuing the search upwards the directory tree until it's found.
     pub fn find(start_dir: &Path) -> RrResult<CargoProj> {
-        let cargo_dir = find_cargo_toml_dir(start_dir)?;
+        let cargo_dir = find_cargo_toml_dir(&start_dir.canonicalize()?)?;
 
         let cargo_toml = cargo_dir.join("Cargo.toml");
         let toml = parse_toml(&cargo_toml)?;
@@ -46,13 +68,25 @@ impl CargoProj {
             Version::parse(version_str)?
         };
 
-        let changelog = find_changelog(&cargo_dir)?;
+        let metadata = rusty_release_metadata(&toml, "package");
+
+        let (workspace_toml, workspace_metadata) = match find_workspace_toml(&cargo_dir, &toml)? {
+            Some((workspace_toml, workspace)) => {
+                let workspace_metadata = rusty_release_metadata(&workspace, "workspace");
+                (workspace_toml, workspace_metadata)
+            },
+
+            None => (cargo_toml.clone(), None)
+        };
 
         Ok(CargoProj {
             name: name.to_string(),
-            version: version,
-            cargo_toml: cargo_toml,
-            changelog: changelog
+            version,
+            cargo_toml,
+            changelog: None,
+            metadata,
+            workspace_toml,
+            workspace_metadata
         })
     }
 
@@ -80,14 +114,111 @@ impl CargoProj {
         Ok(())
     }
 
+    /// Checks that the `Cargo.toml` allows publishing and contains
+    /// the metadata required by crates.io.
+    pub fn check_publishable(&self) -> RrResult<()> {
+        let toml = parse_toml(&self.cargo_toml)?;
+        let package = toml.get("package")
+            .ok_or(format!("Couldn't get 'package' table from: {:?}", toml))?;
+
+        if let Some(&toml::Value::Boolean(false)) = package.get("publish") {
+            return Err(format!("Publishing is disabled by 'publish = false' in '{}'!", self.cargo_toml.display()).into());
+        }
+
+        let mut missing = Vec::new();
+        if package.get("description").is_none() {
+            missing.push("'description'");
+        }
+
+        if package.get("license").is_none() && package.get("license-file").is_none() {
+            missing.push("'license' or 'license-file'");
+        }
+
+        if ! missing.is_empty() {
+            return Err(format!("Missing {} for publishing in '{}'!", missing.join(" and "), self.cargo_toml.display()).into());
+        }
+
+        Ok(())
+    }
+
     /// The root directory of the cargo project.
     pub fn root_dir(&self) -> RrResult<&Path> {
         self.cargo_toml.parent()
             .ok_or(format!("Couldn't get directory of path: {:?}", self.cargo_toml).into())
     }
 
+    /// The path to the `Cargo.toml`.
+    pub fn cargo_toml(&self) -> &Path {
+        &self.cargo_toml
+    }
+
+    /// The path to the root `Cargo.toml` of the workspace containing the cargo project,
+    /// which is the `Cargo.toml` of the cargo project if it isn't a workspace member.
+    pub fn workspace_toml(&self) -> &Path {
+        &self.workspace_toml
+    }
+
+    /// The target directory of the workspace, which is `$CARGO_TARGET_DIR`
+    /// or the directory 'target' next to the workspace `Cargo.toml`.
+    pub fn target_dir(&self) -> RrResult<PathBuf> {
+        if let Some(target_dir) = env::var_os("CARGO_TARGET_DIR") {
+            return Ok(PathBuf::from(target_dir));
+        }
+
+        self.workspace_toml.parent()
+            .map(|dir| dir.join("target"))
+            .ok_or(format!("Couldn't get directory of path: {:?}", self.workspace_toml).into())
+    }
+
+    /// The settings of the `[package.metadata.rusty-release]` table of the `Cargo.toml`.
+    pub fn metadata(&self) -> Option<&toml::Value> {
+        self.metadata.as_ref()
+    }
+
+    /// The settings of the `[workspace.metadata.rusty-release]` table of the workspace `Cargo.toml`.
+    pub fn workspace_metadata(&self) -> Option<&toml::Value> {
+        self.workspace_metadata.as_ref()
+    }
+
+    /// The path to the `Cargo.lock`, which is in the root directory of the cargo project or -
+    /// for a workspace member - in a parent directory up to the root directory of the workspace.
+    pub fn cargo_lock(&self) -> Option<PathBuf> {
+        let workspace_dir = self.workspace_toml.parent()?;
+        self.cargo_toml.parent()?
+            .ancestors()
+            .take_while(|dir| dir.starts_with(workspace_dir))
+            .map(|dir| dir.join("Cargo.lock"))
+            .find(|cargo_lock| cargo_lock.is_file())
+    }
+
     pub fn changelog(&self) -> Option<&Path> {
-        self.changelog.as_ref().map(|p| p.as_path())
+        self.changelog.as_deref()
+    }
+
+    /// Sets the changelog to `path` - relative to the root directory of the cargo project -
+    /// or if it's `None`, searches for a changelog with one of the `CHANGELOG_NAMES`.
+    ///
+    /// If several changelogs are found, then the one with the most preferred name is taken.
+    pub fn find_changelog(&mut self, path: Option<&Path>) -> RrResult<()> {
+        let root_dir = self.root_dir()?.to_path_buf();
+        if let Some(path) = path {
+            let changelog = root_dir.join(path);
+            if ! changelog.is_file() {
+                return Err(format!("Couldn't find the configured changelog '{}'!", changelog.display()).into());
+            }
+
+            self.changelog = Some(changelog);
+            return Ok(());
+        }
+
+        let candidates = changelog_candidates(&root_dir)?;
+        if candidates.len() > 1 {
+            warnln!("Found several changelogs, using '{}', set 'changelog' to use another one:\n{}",
+                    candidates[0].display(), file_list(&candidates));
+        }
+
+        self.changelog = candidates.into_iter().next();
+        Ok(())
     }
 }
 
@@ -131,12 +262,21 @@ fn find_cargo_toml_dir(start_dir: &Path) -> RrResult<PathBuf> {
     }
 }
 
-/// Searches for an optional changelog file in `dir`.
-fn find_changelog(dir: &Path) -> RrResult<Option<PathBuf>> {
-    for file in read_files!(dir) {
-        if let Some(base_name) = file.file_stem().and_then(OsStr::to_str).map(str::to_lowercase) {
-            if base_name == "changelog" {
-                return Ok(Some(file));
+/// Searches for the root `Cargo.toml` of the workspace containing the cargo project
+/// in `cargo_dir` with the parsed `Cargo.toml` `toml`, which is the first `Cargo.toml` -
+/// starting at `cargo_dir` and continuing upwards - containing a `[workspace]` table.
+fn find_workspace_toml(cargo_dir: &Path, toml: &toml::Value) -> RrResult<Option<(PathBuf, toml::Value)>> {
+    if toml.get("workspace").is_some() {
+        return Ok(Some((cargo_dir.join("Cargo.toml"), toml.clone())));
+    }
+
+    let mut dir = cargo_dir.to_path_buf();
+    while dir.pop() {
+        let workspace_toml = dir.join("Cargo.toml");
+        if workspace_toml.is_file() {
+            let workspace = parse_toml(&workspace_toml)?;
+            if workspace.get("workspace").is_some() {
+                return Ok(Some((workspace_toml, workspace)));
             }
         }
     }
@@ -144,6 +284,63 @@ fn find_changelog(dir: &Path) -> RrResult<Option<PathBuf>> {
     Ok(None)
 }
 
+/// The `[<table>.metadata.rusty-release]` table of the parsed `Cargo.toml` `toml`.
+fn rusty_release_metadata(toml: &toml::Value, table: &str) -> Option<toml::Value> {
+    toml.get(table)
+        .and_then(|t| t.get("metadata"))
+        .and_then(|m| m.get("rusty-release"))
+        .cloned()
+}
+
+/// The changelog files in the root directory `root_dir` of the cargo project, ordered by
+/// the preference of their names: first the `CHANGELOG_NAMES` - ignoring the case - and
+/// then any other file named 'changelog' with an arbitrary extension.
+fn changelog_candidates(root_dir: &Path) -> RrResult<Vec<PathBuf>> {
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    let mut add_candidate = |file: PathBuf| {
+        if ! candidates.iter().any(|c| same_file(c, &file)) {
+            candidates.push(file);
+        }
+    };
+
+    for name in CHANGELOG_NAMES {
+        let path = root_dir.join(name);
+        let (dir, name) = match (path.parent(), path.file_name().and_then(OsStr::to_str)) {
+            (Some(dir), Some(name)) if dir.is_dir() => (dir.to_path_buf(), name.to_lowercase()),
+            _                                       => continue
+        };
+
+        let mut files = read_files!(&dir)
+            .filter(|f| f.file_name().and_then(OsStr::to_str).map(str::to_lowercase).as_ref() == Some(&name))
+            .collect::<Vec<_>>();
+
+        // sort the files to be independent of the order of the directory iteration
+        files.sort();
+        for file in files {
+            add_candidate(file);
+        }
+    }
+
+    let mut others = read_files!(root_dir)
+        .filter(|f| f.file_stem().and_then(OsStr::to_str).map(str::to_lowercase).as_deref() == Some("changelog"))
+        .collect::<Vec<_>>();
+
+    others.sort();
+    for file in others {
+        add_candidate(file);
+    }
+
+    Ok(candidates)
+}
+
+/// If both paths point to the same file, e.g. on a case insensitive file system.
+fn same_file(path1: &Path, path2: &Path) -> bool {
+    match (path1.canonicalize(), path2.canonicalize()) {
+        (Ok(p1), Ok(p2)) => p1 == p2,
+        _                => path1 == path2
+    }
+}
+
 fn parse_toml(path: &Path) -> RrResult<toml::Value> {
     map_file(path, |contents| {
         let val = contents.parse::<toml::Value>()?;
@@ -0,0 +1,355 @@
+use std::path::Path;
+use chrono::Local;
+use semver::Version;
+use rr_result::RrResult;
+use utils::{map_file, modify_file};
+
+/// The header of the changelog section for changes not yet released.
+const UNRELEASED: &str = "Unreleased";
+
+/// Adds a section header for `version` - in the layout of the existing headers - above
+/// the first section of the `changelog`, an 'Unreleased' section header is replaced by it.
+pub fn add_version(changelog: &Path, version: &Version) -> RrResult<()> {
+    let date = Local::now().format("%Y-%m-%d").to_string();
+    modify_file(changelog, |contents| with_version(&contents, version, &date))
+}
+
+/// Adds an 'Unreleased' section header - in the layout of the existing
+/// headers - above the first section of the `changelog`.
+pub fn add_unreleased(changelog: &Path) -> RrResult<()> {
+    modify_file(changelog, |contents| with_unreleased(&contents))
+}
+
+/// Reads the section of `version` from the `changelog`.
+pub fn section(changelog: &Path, version: &Version) -> RrResult<Option<String>> {
+    map_file(changelog, |contents| Ok(version_section(&contents, version)))
+}
+
+/// Replaces `from` by `to` in the section of `version` in the `changelog`.
+pub fn replace_in_section(changelog: &Path, version: &Version, from: &str, to: &str) -> RrResult<()> {
+    modify_file(changelog, |contents| {
+        let lines = contents.split_inclusive('\n').collect::<Vec<&str>>();
+        let start = match lines.iter().position(|l| is_header_of(l, version)) {
+            Some(header) => header + 1,
+            None         => return contents.to_string()
+        };
+
+        let end = lines[start..].iter()
+            .position(|l| separator(l).is_some())
+            .map_or(lines.len(), |i| start + i);
+
+        format!("{}{}{}", lines[..start].concat(), lines[start..end].concat().replace(from, to), lines[end..].concat())
+    })
+}
+
+/// Extracts the section of `version` from the changelog `contents`.
+///
+/// A section starts with the header line of its version and ends before the
+/// header of the next section. The headers of these layouts are supported:
+///
+/// * plain: `1.2.0`, like it's added by `rusty-release`, optionally followed
+///   by a date - e.g. `1.2.0 (2019-02-24)` - and underlined by `---` or `===`
+/// * Markdown headings: `## 1.2.0` or `## v1.2.0 - 2019-02-24`
+/// * Keep a Changelog: `## [1.2.0] - 2019-02-24`, the link reference definitions
+///   at the end - like `[1.2.0]: https://...` - aren't part of the last section
+fn version_section(contents: &str, version: &Version) -> Option<String> {
+    let mut lines = contents.lines()
+        .skip_while(|l| ! is_header_of(l, version))
+        .skip(1)
+        .peekable();
+
+    if lines.peek().map(|l| is_underline(l)) == Some(true) {
+        lines.next();
+    }
+
+    let section = lines.take_while(|l| separator(l).is_none())
+        .collect::<Vec<&str>>()
+        .join("\n");
+
+    let section = section.trim();
+    if section.is_empty() {
+        None
+    } else {
+        Some(section.to_string())
+    }
+}
+
+/// Adds the section header of `version` released at `date` to the changelog `contents`.
+fn with_version(contents: &str, version: &Version, date: &str) -> String {
+    let lines = contents.split_inclusive('\n').collect::<Vec<&str>>();
+    let layout = Layout::of(&lines);
+    let header = layout.version_header(version, date);
+    match first_header(&lines) {
+        Some(i) if is_unreleased(lines[i]) => {
+            let end = if lines.get(i + 1).map(|l| is_underline(l)) == Some(true) { i + 2 } else { i + 1 };
+            format!("{}{}\n{}", lines[..i].concat(), header, lines[end..].concat())
+        }
+
+        _ => insert_header(&lines, &header)
+    }
+}
+
+/// Adds an 'Unreleased' section header to the changelog `contents`, if there's none.
+fn with_unreleased(contents: &str) -> String {
+    let lines = contents.split_inclusive('\n').collect::<Vec<&str>>();
+    if lines.iter().any(|l| is_unreleased(l)) {
+        return contents.to_string();
+    }
+
+    insert_header(&lines, &Layout::of(&lines).unreleased_header())
+}
+
+/// Inserts `header` above the first section header of the changelog `lines`,
+/// or below the title of a changelog without sections.
+fn insert_header(lines: &[&str], header: &str) -> String {
+    let at = first_header(lines).unwrap_or_else(|| {
+        if lines.first().map(|l| l.starts_with('#')) == Some(true) {
+            1 + lines[1..].iter().take_while(|l| l.trim().is_empty()).count()
+        } else {
+            0
+        }
+    });
+
+    let separator = match lines[..at].last() {
+        None                           => "",
+        Some(l) if l.trim().is_empty() => "",
+        Some(l) if l.ends_with('\n')   => "\n",
+        Some(_)                        => "\n\n"
+    };
+
+    format!("{}{}{}\n\n{}", lines[..at].concat(), separator, header, lines[at..].concat())
+}
+
+/// The index of the first section header in the changelog `lines`.
+fn first_header(lines: &[&str]) -> Option<usize> {
+    lines.iter().position(|l| matches!(separator(l), Some((_, false))))
+}
+
+fn is_unreleased(line: &str) -> bool {
+    matches!(separator(line), Some((name, false)) if name.eq_ignore_ascii_case(UNRELEASED))
+}
+
+/// How a date follows the version in a section header.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DateStyle {
+    None,
+    /// e.g. `1.2.0 - 2019-02-24`
+    Dash,
+    /// e.g. `1.2.0 (2019-02-24)`
+    Parens
+}
+
+/// The layout of the section headers of a changelog, so that added
+/// headers look like the existing ones.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Layout {
+    /// the number of '#' of a Markdown heading, 0 for a plain header
+    level: usize,
+
+    /// if the version is in brackets, like by Keep a Changelog
+    bracketed: bool,
+
+    /// if the version is prefixed by 'v'
+    v_prefix: bool,
+
+    date: DateStyle
+}
+
+impl Layout {
+    /// The layout of the first version header of the changelog `lines`, or of its 'Unreleased'
+    /// header. Without any, it's a Markdown heading below a title, otherwise a plain header.
+    fn of(lines: &[&str]) -> Layout {
+        let headers = lines.iter()
+            .enumerate()
+            .filter(|(_, l)| matches!(separator(l), Some((_, false))))
+            .map(|(i, _)| i)
+            .collect::<Vec<usize>>();
+
+        let header = headers.iter()
+            .find(|i| ! is_unreleased(lines[**i]))
+            .or_else(|| headers.first());
+
+        match header {
+            Some(&i) => Layout::of_header(lines[i]),
+            None     => {
+                let level = if lines.first().map(|l| l.starts_with('#')) == Some(true) { 2 } else { 0 };
+                Layout { level, bracketed: false, v_prefix: false, date: DateStyle::None }
+            }
+        }
+    }
+
+    fn of_header(header: &str) -> Layout {
+        let line = header.trim();
+        let level = line.chars().take_while(|c| *c == '#').count();
+        let line = line[level..].trim_start();
+        let bracketed = line.starts_with('[');
+        let line = line.trim_start_matches('[');
+        let rest = if bracketed {
+            line.find(']').map_or("", |end| &line[end + 1..])
+        } else {
+            line.find(char::is_whitespace).map_or("", |end| &line[end..])
+        };
+
+        let rest = rest.trim_start();
+        let date = if rest.starts_with('-') {
+            DateStyle::Dash
+        } else if rest.starts_with('(') {
+            DateStyle::Parens
+        } else if bracketed && is_unreleased(header) {
+            // the versions of Keep a Changelog are always dated
+            DateStyle::Dash
+        } else {
+            DateStyle::None
+        };
+
+        Layout { level, bracketed, v_prefix: line.starts_with('v'), date }
+    }
+
+    fn version_header(&self, version: &Version, date: &str) -> String {
+        let name = if self.v_prefix { format!("v{}", version) } else { version.to_string() };
+        let date = match self.date {
+            DateStyle::None   => String::new(),
+            DateStyle::Dash   => format!(" - {}", date),
+            DateStyle::Parens => format!(" ({})", date)
+        };
+
+        self.header(&name, &date)
+    }
+
+    fn unreleased_header(&self) -> String {
+        self.header(UNRELEASED, "")
+    }
+
+    fn header(&self, name: &str, date: &str) -> String {
+        let prefix = if self.level > 0 { format!("{} ", "#".repeat(self.level)) } else { String::new() };
+        if self.bracketed {
+            format!("{}[{}]{}", prefix, name, date)
+        } else {
+            format!("{}{}{}", prefix, name, date)
+        }
+    }
+}
+
+fn is_header_of(line: &str, version: &Version) -> bool {
+    match separator(line) {
+        Some((name, false)) => Version::parse(name).ok().as_ref() == Some(version),
+        _                   => false
+    }
+}
+
+/// If `line` separates the sections of a changelog, then the version - or 'Unreleased' - it's
+/// naming and if it's a Markdown link reference definition instead of a section header.
+fn separator(line: &str) -> Option<(&str, bool)> {
+    let line = line.trim();
+    let is_heading = line.starts_with('#');
+    let line = line.trim_start_matches('#').trim_start();
+
+    let (name, rest) = if let Some(bracketed) = line.strip_prefix('[') {
+        let end = bracketed.find(']')?;
+        (&bracketed[..end], bracketed[end + 1..].trim_start())
+    } else {
+        let end = line.find(char::is_whitespace).unwrap_or(line.len());
+        (&line[..end], line[end..].trim_start())
+    };
+
+    let is_link_definition = ! is_heading && rest.starts_with(':');
+
+    // a plain header might only be followed by a date, so that
+    // text starting with a version isn't taken as a header
+    let is_plain_header = rest.is_empty() || rest.starts_with('-') || rest.starts_with('(');
+    if ! (is_heading || is_link_definition || is_plain_header) {
+        return None;
+    }
+
+    let name = name.strip_prefix('v').unwrap_or(name);
+    if name.eq_ignore_ascii_case(UNRELEASED) || Version::parse(name).is_ok() {
+        Some((name, is_link_definition))
+    } else {
+        None
+    }
+}
+
+fn is_underline(line: &str) -> bool {
+    let line = line.trim();
+    ! line.is_empty() && (line.chars().all(|c| c == '-') || line.chars().all(|c| c == '='))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile;
+
+    fn section(contents: &str, version: &str) -> Option<String> {
+        version_section(contents, &Version::parse(version).unwrap())
+    }
+
+    #[test]
+    fn plain_layout() {
+        let contents = "0.2.0 (2019-02-24)\n\n* Add foo\n\n0.1.0\n-----\n* Initial release\n";
+        assert_eq!(section(contents, "0.2.0").as_deref(), Some("* Add foo"));
+        assert_eq!(section(contents, "0.1.0").as_deref(), Some("* Initial release"));
+        assert_eq!(section("0.2.0\n\n0.1.0\n* Initial release\n", "0.2.0"), None);
+        assert_eq!(section(contents, "0.3.0"), None);
+    }
+
+    #[test]
+    fn markdown_heading_layout() {
+        let contents = "# Changelog\n\n## v0.2.0 - 2019-02-24\n\n### Fixed\n* 1.0.0 compatibility\n\n## 0.1.0\n* Initial release\n";
+        assert_eq!(section(contents, "0.2.0").as_deref(), Some("### Fixed\n* 1.0.0 compatibility"));
+        assert_eq!(section(contents, "0.1.0").as_deref(), Some("* Initial release"));
+    }
+
+    #[test]
+    fn replaces_only_in_section_of_version() {
+        let mut changelog = tempfile::NamedTempFile::new().unwrap();
+        write!(changelog, "## 0.2.0\nThanks to <X>\n\n## 0.1.0\nThanks to <X>\n").unwrap();
+
+        replace_in_section(changelog.path(), &Version::parse("0.2.0").unwrap(), "<X>", "Alice").unwrap();
+        assert_eq!(std::fs::read_to_string(changelog.path()).unwrap(), "## 0.2.0\nThanks to Alice\n\n## 0.1.0\nThanks to <X>\n");
+    }
+
+    fn with_version(contents: &str, version: &str) -> String {
+        super::with_version(contents, &Version::parse(version).unwrap(), "2019-03-01")
+    }
+
+    #[test]
+    fn adds_version_in_layout_of_changelog() {
+        assert_eq!(with_version("0.1.0\n-----\n* Initial release\n", "0.1.1"), "0.1.1\n\n0.1.0\n-----\n* Initial release\n");
+        assert_eq!(with_version("Unreleased\n* Add foo\n\n0.1.0\n", "0.2.0"), "0.2.0\n* Add foo\n\n0.1.0\n");
+        assert_eq!(with_version("# Changelog\n\n## v0.1.0 (2019-01-01)\n* Initial release\n", "0.1.1"),
+                   "# Changelog\n\n## v0.1.1 (2019-03-01)\n\n## v0.1.0 (2019-01-01)\n* Initial release\n");
+        assert_eq!(with_version("# Changelog\n", "0.1.0"), "# Changelog\n\n## 0.1.0\n\n");
+        assert_eq!(with_version("", "0.1.0"), "0.1.0\n\n");
+    }
+
+    #[test]
+    fn adds_version_to_keep_a_changelog() {
+        let contents = "# Changelog\n\n## [Unreleased]\n* Add bar\n\n## [0.1.0] - 2019-01-01\n* Initial release\n";
+        assert_eq!(with_version(contents, "0.2.0"),
+                   "# Changelog\n\n## [0.2.0] - 2019-03-01\n* Add bar\n\n## [0.1.0] - 2019-01-01\n* Initial release\n");
+
+        let contents = "# Changelog\nAll notable changes.\n\n## [0.1.0] - 2019-01-01\n* Initial release\n";
+        assert_eq!(with_version(contents, "0.1.1"),
+                   "# Changelog\nAll notable changes.\n\n## [0.1.1] - 2019-03-01\n\n## [0.1.0] - 2019-01-01\n* Initial release\n");
+        assert_eq!(with_version("# Changelog\n\n## [Unreleased]\n", "0.1.0"), "# Changelog\n\n## [0.1.0] - 2019-03-01\n");
+    }
+
+    #[test]
+    fn adds_unreleased_in_layout_of_changelog() {
+        assert_eq!(with_unreleased("0.1.0\n* Initial release\n"), "Unreleased\n\n0.1.0\n* Initial release\n");
+        assert_eq!(with_unreleased("# Changelog\n\n## [0.1.0] - 2019-01-01\n"), "# Changelog\n\n## [Unreleased]\n\n## [0.1.0] - 2019-01-01\n");
+        assert_eq!(with_unreleased("# Changelog\n\n## v0.1.0\n"), "# Changelog\n\n## Unreleased\n\n## v0.1.0\n");
+        assert_eq!(with_unreleased("## Unreleased\n\n## 0.1.0\n"), "## Unreleased\n\n## 0.1.0\n");
+    }
+
+    #[test]
+    fn keep_a_changelog_layout() {
+        let contents = "# Changelog\n\n## [Unreleased]\n* Add bar\n\n## [0.2.0] - 2019-02-24\n### Added\n* Add foo\n\n\
+                        ## [0.1.0] - 2019-01-01\n* Initial release\n\n\
+                        [Unreleased]: https://example.com/compare/v0.2.0...HEAD\n\
+                        [0.2.0]: https://example.com/compare/v0.1.0...v0.2.0\n";
+        assert_eq!(section(contents, "0.2.0").as_deref(), Some("### Added\n* Add foo"));
+        assert_eq!(section(contents, "0.1.0").as_deref(), Some("* Initial release"));
+    }
+}
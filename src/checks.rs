@@ -0,0 +1,42 @@
+use std::thread;
+use rr_result::RrResult;
+
+/// An independent check running before the release.
+pub struct Check<'a> {
+    /// the name of the check used in the report
+    name: &'static str,
+
+    /// the function running the check
+    run: Box<dyn FnOnce() -> RrResult<()> + Send + 'a>
+}
+
+impl<'a> Check<'a> {
+    pub fn new<F>(name: &'static str, run: F) -> Check<'a>
+        where F: FnOnce() -> RrResult<()> + Send + 'a
+    {
+        Check { name, run: Box::new(run) }
+    }
+}
+
+/// Runs the `checks` in parallel and reports the failures of all of them.
+pub fn run_parallel(checks: Vec<Check>) -> RrResult<()> {
+    let results = thread::scope(|scope| {
+        let handles = checks.into_iter()
+            .map(|c| (c.name, scope.spawn(c.run)))
+            .collect::<Vec<_>>();
+
+        handles.into_iter()
+            .map(|(name, handle)| (name, handle.join().unwrap_or_else(|_| Err("Check panicked!".into()))))
+            .collect::<Vec<_>>()
+    });
+
+    let failures = results.into_iter()
+        .filter_map(|(name, result)| result.err().map(|err| format!("{}: {}", name, err.to_string().trim())))
+        .collect::<Vec<_>>();
+
+    if failures.is_empty() {
+        return Ok(());
+    }
+
+    Err(format!("{} of the checks failed:\n\n{}", failures.len(), failures.join("\n")).into())
+}
@@ -0,0 +1,362 @@
+use std::io::Write;
+use tempfile::{Builder, NamedTempFile};
+use rr_result::RrResult;
+use git::{Git, LogEntry};
+use template::{Template, Vars};
+
+/// The placeholders supported by the format of the commits.
+pub const PLACEHOLDERS: &[&str] = &[
+    "HASH",
+    "AUTHOR",
+    "DATE",
+    "SUBJECT",
+    "REFS"
+];
+
+/// The conventional commit types and the titles of their groups, in the order of the groups.
+const TYPES: &[(&str, &str)] = &[
+    ("feat",     "Features"),
+    ("fix",      "Bug Fixes"),
+    ("perf",     "Performance"),
+    ("refactor", "Refactoring"),
+    ("docs",     "Documentation"),
+    ("test",     "Tests"),
+    ("build",    "Build"),
+    ("ci",       "Continuous Integration"),
+    ("style",    "Style"),
+    ("chore",    "Chores"),
+    ("revert",   "Reverts")
+];
+
+const BREAKING_CHANGES: &str = "Breaking Changes";
+
+/// The group of the commits which don't fit into any other group.
+const OTHER: &str = "Other";
+
+// How the commits of the log are grouped
+arg_enum! {
+    #[derive(Eq, PartialEq, Debug, Clone, Copy)]
+    pub enum LogGroup {
+        // not grouped
+        None,
+
+        // by the type of conventional commits, e.g. 'feat' or 'fix'
+        Type,
+
+        // by the top-level path - relative to the cargo project - changed by the commit
+        Path
+    }
+}
+
+/// Writes the log of the commits since the previous release `prev_tag_name` - or of all
+/// commits for the first release - into a temporary file, each commit formatted by `format`
+/// and grouped by `group`. If the cargo project resides in the sub directory `crate_dir`
+/// of the git repository, then only the commits touching it are considered.
+pub fn write(git: &dyn Git, prev_tag_name: Option<&str>, crate_dir: &str, format: &str, group: LogGroup) -> RrResult<NamedTempFile> {
+    let path = if crate_dir.is_empty() { None } else { Some(".") };
+    let commits = git.commits("HEAD", prev_tag_name, path)?;
+
+    let (header, prefix) = if let Some(prev_tag_name) = prev_tag_name {
+        (format!("Commits since the previous release {}:", prev_tag_name), format!("{}..HEAD___", prev_tag_name))
+    } else {
+        ("First release, all commits:".to_string(), "HEAD".to_string())
+    };
+
+    let mut log_file = Builder::new()
+        .prefix(&prefix)
+        .tempfile()?;
+
+    writeln!(log_file, "{}\n\n{}", header, render(&commits, format, group, crate_dir))?;
+    Ok(log_file)
+}
+
+/// Renders the `commits` by `format`, one commit per line, grouped by `group` - the groups are
+/// separated by an empty line and start with a title line, followed by their indented commits.
+fn render(commits: &[LogEntry], format: &str, group: LogGroup, crate_dir: &str) -> String {
+    let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+    let mut add = |title: &str, line: String| {
+        match groups.iter_mut().find(|(t, _)| t == title) {
+            Some((_, lines)) => lines.push(line),
+            None             => groups.push((title.to_string(), vec![line]))
+        }
+    };
+
+    for commit in commits {
+        let vars = CommitVars::new(commit, group == LogGroup::Type);
+        let line = Template(format).render(&vars).trim_end().to_string();
+        match group {
+            LogGroup::None => add("", line),
+            LogGroup::Type => add(&type_title(commit, &vars.conventional), line),
+            LogGroup::Path => {
+                let mut titles = top_level_paths(commit, crate_dir);
+                if titles.is_empty() {
+                    titles.push(OTHER.to_string());
+                }
+
+                for title in titles {
+                    add(&title, line.clone());
+                }
+            }
+        }
+    }
+
+    groups.sort_by_key(|(title, _)| group_order(group, title));
+    groups.into_iter()
+        .map(|(title, lines)| {
+            if title.is_empty() {
+                lines.join("\n")
+            } else {
+                format!("{}:\n  {}", title, lines.join("\n  "))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// The position of the group with `title`: the breaking changes first, then the
+/// conventional commit types in the order of `TYPES` or the paths sorted by name,
+/// and the other commits last.
+fn group_order(group: LogGroup, title: &str) -> (usize, String) {
+    if title == BREAKING_CHANGES {
+        return (0, String::new());
+    }
+
+    if title == OTHER {
+        return (TYPES.len() + 2, String::new());
+    }
+
+    match group {
+        LogGroup::Type => (TYPES.iter().position(|&(_, t)| t == title).unwrap_or(0) + 1, String::new()),
+        _              => (1, title.to_string())
+    }
+}
+
+fn type_title(commit: &LogEntry, conventional: &Option<Conventional>) -> String {
+    let title = match *conventional {
+        Some(ref c) if c.breaking || commit.body.contains("BREAKING CHANGE") => BREAKING_CHANGES,
+        Some(ref c) => TYPES.iter()
+            .find(|&&(kind, _)| kind == c.kind.to_lowercase())
+            .map(|&(_, title)| title)
+            .unwrap_or(OTHER),
+
+        None => OTHER
+    };
+
+    title.to_string()
+}
+
+/// The top-level paths changed by `commit`, relative to `crate_dir` - the directory
+/// of the cargo project in the git repository - with a trailing '/' for directories.
+fn top_level_paths(commit: &LogEntry, crate_dir: &str) -> Vec<String> {
+    let crate_prefix = format!("{}/", crate_dir);
+    let mut top_level_paths = Vec::new();
+    for path in &commit.paths {
+        let path = if crate_dir.is_empty() { path.as_str() } else { path.strip_prefix(&crate_prefix).unwrap_or(path) };
+        let top_level_path = match path.find('/') {
+            Some(i) => path[..=i].to_string(),
+            None    => path.to_string()
+        };
+
+        if ! top_level_paths.contains(&top_level_path) {
+            top_level_paths.push(top_level_path);
+        }
+    }
+
+    top_level_paths
+}
+
+/// The values for the placeholders of the format of a commit.
+struct CommitVars<'a> {
+    commit: &'a LogEntry,
+
+    /// the subject - for a merged pull/merge request the title of the request - without
+    /// the references of `refs` and with `conventional_subject` without the type of a
+    /// conventional commit
+    subject: String,
+
+    conventional: Option<Conventional>,
+
+    /// the references to pull/merge requests and issues, e.g. '#12' or '!7'
+    refs: Vec<String>
+}
+
+impl<'a> CommitVars<'a> {
+    fn new(commit: &'a LogEntry, conventional_subject: bool) -> CommitVars<'a> {
+        let mut subject = commit.subject.clone();
+        let is_merge_request = subject.starts_with("Merge pull request #")
+            || (subject.starts_with("Merge branch ") && commit.body.contains("See merge request "));
+        if is_merge_request {
+            if let Some(title) = commit.body.lines().next().filter(|l| ! l.trim().is_empty()) {
+                subject = title.trim().to_string();
+            }
+        }
+
+        let conventional = Conventional::parse(&subject);
+        if conventional_subject {
+            if let Some(ref c) = conventional {
+                subject = match c.scope {
+                    Some(ref scope) => format!("{}: {}", scope, c.description),
+                    None            => c.description.clone()
+                };
+            }
+        }
+
+        let refs = refs(commit);
+        CommitVars { commit, subject: strip_refs(&subject, &refs), conventional, refs }
+    }
+}
+
+impl<'a> Vars for CommitVars<'a> {
+    fn value(&self, placeholder: &str) -> Option<String> {
+        let value = match placeholder {
+            "HASH"    => self.commit.hash.clone(),
+            "AUTHOR"  => self.commit.author.clone(),
+            "DATE"    => self.commit.date.clone(),
+            "SUBJECT" => self.subject.clone(),
+            "REFS"    => self.refs.join(" "),
+            _         => return None
+        };
+
+        Some(value)
+    }
+}
+
+/// The subject of a conventional commit, like 'feat(parser)!: add foo'.
+#[derive(Debug, PartialEq)]
+struct Conventional {
+    kind: String,
+    scope: Option<String>,
+    breaking: bool,
+    description: String
+}
+
+impl Conventional {
+    fn parse(subject: &str) -> Option<Conventional> {
+        let colon = subject.find(": ")?;
+        let prefix = &subject[..colon];
+        let (prefix, breaking) = match prefix.strip_suffix('!') {
+            Some(prefix) => (prefix, true),
+            None         => (prefix, false)
+        };
+
+        let (kind, scope) = match prefix.find('(') {
+            Some(open) => (&prefix[..open], Some(prefix[open + 1..].strip_suffix(')')?.to_string())),
+            None       => (prefix, None)
+        };
+
+        if kind.is_empty() || ! kind.chars().all(|c| c.is_ascii_alphabetic()) {
+            return None;
+        }
+
+        Some(Conventional {
+            kind: kind.to_string(),
+            scope,
+            breaking,
+            description: subject[colon + 2..].trim().to_string()
+        })
+    }
+}
+
+/// The references to pull requests and issues - like '#12' - in the subject of `commit`
+/// and to GitLab merge requests - like '!7' - in the 'See merge request' line of its body.
+fn refs(commit: &LogEntry) -> Vec<String> {
+    let mut refs = Vec::new();
+    let mut add = |r: String| if ! refs.contains(&r) { refs.push(r) };
+
+    let subject = commit.subject.as_bytes();
+    for (i, _) in commit.subject.match_indices('#') {
+        let preceded_by_word = i > 0 && subject[i - 1].is_ascii_alphanumeric();
+        let digits = commit.subject[i + 1..].chars().take_while(char::is_ascii_digit).collect::<String>();
+        if ! preceded_by_word && ! digits.is_empty() {
+            add(format!("#{}", digits));
+        }
+    }
+
+    for line in commit.body.lines() {
+        if let Some(request) = line.trim().strip_prefix("See merge request ") {
+            if let Some(i) = request.rfind('!') {
+                add(request[i..].to_string());
+            }
+        }
+    }
+
+    refs
+}
+
+/// Removes the words of `subject` which are one of `refs` - optionally in parentheses,
+/// like '(#12)' - so that they aren't repeated by '<SUBJECT> <REFS>'.
+fn strip_refs(subject: &str, refs: &[String]) -> String {
+    subject.split_whitespace()
+        .filter(|word| {
+            let is_balanced = word.matches('(').count() == word.matches(')').count();
+            let word = word.trim_matches(|c| c == '(' || c == ')');
+            ! (is_balanced && refs.iter().any(|r| r == word))
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(hash: &str, subject: &str, body: &str, paths: &[&str]) -> LogEntry {
+        LogEntry {
+            hash: hash.to_string(),
+            author: "Alice".to_string(),
+            date: "2019-02-24".to_string(),
+            subject: subject.to_string(),
+            body: body.to_string(),
+            paths: paths.iter().map(|p| p.to_string()).collect()
+        }
+    }
+
+    fn commits() -> Vec<LogEntry> {
+        vec![
+            commit("aaa", "Merge pull request #12 from bob/parser", "feat(parser): support comments", &[]),
+            commit("bbb", "fix: crash for empty input (#11)", "", &["crates/foo/src/lib.rs", "crates/foo/Cargo.toml"]),
+            commit("ccc", "feat!: remove the old api", "", &["crates/foo/src/api.rs"]),
+            commit("ddd", "Update readme", "See merge request group/foo!7", &["crates/foo/README.md"])
+        ]
+    }
+
+    #[test]
+    fn renders_commits_with_references() {
+        assert_eq!(render(&commits(), "<HASH> <SUBJECT> (<AUTHOR>, <DATE>) <REFS>", LogGroup::None, ""),
+                   "aaa feat(parser): support comments (Alice, 2019-02-24) #12\n\
+                    bbb fix: crash for empty input (Alice, 2019-02-24) #11\n\
+                    ccc feat!: remove the old api (Alice, 2019-02-24)\n\
+                    ddd Update readme (Alice, 2019-02-24) !7");
+    }
+
+    #[test]
+    fn groups_by_conventional_commit_type() {
+        assert_eq!(render(&commits(), "<HASH> <SUBJECT>", LogGroup::Type, ""),
+                   "Breaking Changes:\n  ccc remove the old api\n\n\
+                    Features:\n  aaa parser: support comments\n\n\
+                    Bug Fixes:\n  bbb crash for empty input\n\n\
+                    Other:\n  ddd Update readme");
+    }
+
+    #[test]
+    fn groups_by_top_level_path() {
+        assert_eq!(render(&commits(), "<HASH>", LogGroup::Path, "crates/foo"),
+                   "Cargo.toml:\n  bbb\n\n\
+                    README.md:\n  ddd\n\n\
+                    src/:\n  bbb\n  ccc\n\n\
+                    Other:\n  aaa");
+    }
+
+    #[test]
+    fn parses_conventional_commits() {
+        assert_eq!(Conventional::parse("feat(parser)!: add foo"), Some(Conventional {
+            kind: "feat".to_string(),
+            scope: Some("parser".to_string()),
+            breaking: true,
+            description: "add foo".to_string()
+        }));
+
+        assert_eq!(Conventional::parse("Add foo: the bar"), None);
+        assert_eq!(Conventional::parse("fix(parser: add foo"), None);
+    }
+}
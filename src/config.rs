@@ -1,12 +1,20 @@
 use std::env;
+use std::fmt::{self, Display, Formatter};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use clap::{App, Arg};
+use clap::{App, AppSettings, Arg, SubCommand};
+use semver::Version;
 use toml;
 use dirs;
-use rr_result::RrResult;
+use strsim::levenshtein;
+use rr_result::{RrResult, RrError};
 use version_kind::VersionKind;
+use git::GitBackend;
+use commit_log::{self, LogGroup};
 use utils::map_file;
+use editor;
+use template::{Template, TemplateVars};
 use cargo_proj::CargoProj;
 
 /// The configuration used to run `rusty-release`.
@@ -15,8 +23,31 @@ pub struct Config {
     /// which part of the project version should be incremented
     pub version_kind: VersionKind,
 
-    /// start directory for the search of the 'Cargo.toml'
-    pub start_dir: PathBuf,
+    /// only print the configuration
+    pub print_config: bool,
+
+    /// only check the configuration
+    pub check_config: bool,
+
+    /// only print the release notes of this version from the changelog
+    pub notes: Option<Version>,
+
+    /// run without network access and defer the git push
+    /// and the publishing till `finish`
+    pub offline: bool,
+
+    /// complete the git push and the publishing of an offline release
+    pub finish: bool,
+
+    /// don't ask for confirmation before making the release
+    pub yes: bool,
+
+    /// log the run git and cargo commands
+    pub verbose: bool,
+
+    /// only run the checks, print the release plan and the run
+    /// git and cargo commands, but don't change anything
+    pub dry_run: bool,
 
     /// publish to crates.io
     pub cargo_publish: bool,
@@ -24,16 +55,166 @@ pub struct Config {
     /// push to git remote repository
     pub git_push: bool,
 
+    /// the registry to publish to instead of crates.io
+    pub registry: Option<String>,
+
     /// string template for the commit message
     commit_message: String,
 
-    /// string template for the tag name
-    tag_name: String,
+    /// string template for the tag name, if `None` the default depends on
+    /// the cargo project residing in a sub directory of the git repository
+    tag_name: Option<String>,
+
+    /// string template for the message of the annotated tag
+    tag_message: String,
+
+    /// sign the release commit
+    pub sign_commit: bool,
+
+    /// sign the release tag
+    pub sign_tag: bool,
+
+    /// which part of the released version is incremented for the development
+    /// version, no development version is created if it's `None`
+    pub dev_version: Option<VersionKind>,
+
+    /// the pre-release part of the development version
+    pub dev_version_pre: String,
+
+    /// string template for the commit message of the development version
+    dev_commit_message: String,
+
+    /// only report untracked files instead of failing
+    pub allow_untracked: bool,
+
+    /// only report files modified during the release - other than the ones
+    /// changed by the release itself - instead of failing
+    pub allow_unexpected_changes: bool,
 
     /// the editor command, used for opening of the changelog
-    editor: String
+    editor: String,
+
+    /// open a file containing the commits since the previous release next to the changelog
+    pub commit_log: bool,
+
+    /// string template for a commit in the commit log
+    pub log_format: String,
+
+    /// how the commits in the commit log are grouped
+    pub log_group: LogGroup,
+
+    /// the implementation of the git operations
+    pub git_backend: GitBackend,
+
+    /// the path to the changelog relative to the root directory of the cargo
+    /// project, if `None` the changelog is searched by well-known names
+    pub changelog: Option<PathBuf>,
+
+    /// fail the release if there's no changelog
+    pub changelog_required: bool,
+
+    /// where the settings are coming from, settings not contained
+    /// are coming from the defaults
+    sources: BTreeMap<String, Source>
+}
+
+/// The source of a configuration setting.
+#[derive(Clone, Debug, PartialEq)]
+enum Source {
+    /// the default value of `rusty-release`
+    Default,
+
+    /// the configuration file in the home directory
+    HomeFile(PathBuf),
+
+    /// the configuration file in the user configuration directory
+    UserConfigFile(PathBuf),
+
+    /// a configuration file of the cargo project, its workspace or its git repository
+    ProjectFile(PathBuf),
+
+    /// the `[package.metadata.rusty-release]` table of a `Cargo.toml`
+    PackageMetadata(PathBuf),
+
+    /// the `[workspace.metadata.rusty-release]` table of a `Cargo.toml`
+    WorkspaceMetadata(PathBuf),
+
+    /// an environment variable
+    Environment(String),
+
+    /// a command line flag
+    CommandLine(&'static str)
+}
+
+impl Source {
+    /// The configuration file or `Cargo.toml` of the setting, if it's coming from one.
+    fn path(&self) -> Option<&Path> {
+        match *self {
+            Source::HomeFile(ref path)          |
+            Source::UserConfigFile(ref path)    |
+            Source::ProjectFile(ref path)       |
+            Source::PackageMetadata(ref path)   |
+            Source::WorkspaceMetadata(ref path) => Some(path),
+            _                                   => None
+        }
+    }
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            Source::Default                     => write!(f, "default"),
+            Source::HomeFile(ref path)          => write!(f, "home file '{}'", path.display()),
+            Source::UserConfigFile(ref path)    => write!(f, "user config file '{}'", path.display()),
+            Source::ProjectFile(ref path)       => write!(f, "project file '{}'", path.display()),
+            Source::PackageMetadata(ref path)   => write!(f, "[package.metadata.rusty-release] of '{}'", path.display()),
+            Source::WorkspaceMetadata(ref path) => write!(f, "[workspace.metadata.rusty-release] of '{}'", path.display()),
+            Source::Environment(ref var)        => write!(f, "environment variable '{}'", var),
+            Source::CommandLine(flag)           => write!(f, "command line flag '{}'", flag)
+        }
+    }
 }
 
+/// The names of the settings of a configuration file.
+const SETTINGS: &[&str] = &[
+    "cargo_publish",
+    "git_push",
+    "registry",
+    "commit_message",
+    "tag_name",
+    "tag_message",
+    "sign_commit",
+    "sign_tag",
+    "dev_version",
+    "dev_version_pre",
+    "dev_commit_message",
+    "allow_untracked",
+    "allow_unexpected_changes",
+    "editor",
+    "commit_log",
+    "log_format",
+    "log_group",
+    "git_backend",
+    "changelog",
+    "changelog_required"
+];
+
+/// The settings with a boolean value.
+const BOOL_SETTINGS: &[&str] = &[
+    "cargo_publish",
+    "git_push",
+    "sign_commit",
+    "sign_tag",
+    "allow_untracked",
+    "allow_unexpected_changes",
+    "commit_log",
+    "changelog_required"
+];
+
+/// The prefix of the environment variables overriding a setting,
+/// e.g. `RUSTY_RELEASE_CARGO_PUBLISH` for `cargo_publish`.
+const ENV_PREFIX: &str = "RUSTY_RELEASE_";
+
 /// Helper macro to apply the settings from ConfigFromFile to Config
 macro_rules! config {
     ( $file_config:ident, [ $( $field_name:ident ),* ]) => {{
@@ -48,13 +229,39 @@ macro_rules! config {
     }}
 }
 
-impl Config {
-   pub fn from_file_and_command_args() -> RrResult<Config> {
+/// The command line arguments of `rusty-release`.
+#[derive(Debug)]
+pub struct CommandArgs {
+    /// start directory for the search of the 'Cargo.toml'
+    pub start_dir: PathBuf,
+
+    version_kind: Option<VersionKind>,
+    print_config: bool,
+    check_config: bool,
+
+    /// the version given to the 'notes' subcommand, `Some(None)` for the current version
+    notes: Option<Option<Version>>,
+
+    offline: bool,
+    finish: bool,
+    yes: bool,
+    verbose: bool,
+    dry_run: bool,
+
+    /// the settings given by command line flags
+    cli_config: ConfigFromFile
+}
+
+impl CommandArgs {
+   pub fn parse() -> RrResult<CommandArgs> {
        let matches = App::new("rusty-release")
            .about("Make a release for a cargo project")
            .version(crate_version!())
            .author("Daniel Trstenjak <daniel.trstenjak@gmail.com>")
-           .arg_from_usage("<VERSION_KIND> 'Which version number gets increased (major, minor, patch or current)'")
+           .setting(AppSettings::SubcommandsNegateReqs)
+           .arg(Arg::with_name("VERSION_KIND")
+                .help("Which version number gets increased (major, minor, patch or current)")
+                .required_unless_one(&["print-config", "finish"]))
            .arg(Arg::with_name("start-dir")
                 .short("s")
                 .long("start-dir")
@@ -63,6 +270,22 @@ impl Config {
                 .takes_value(true))
            .arg_from_usage("-n --no-cargo-publish 'Do not publish to crates.io'")
            .arg_from_usage("-N --no-git-push 'Do not push to remote git repository'")
+           .arg_from_usage("-y --yes 'Do not ask for confirmation before making the release'")
+           .arg_from_usage("-v --verbose 'Log every run git and cargo command with its exit code and running time'")
+           .arg(Arg::from_usage("--dry-run 'Only run the checks and print the release plan and the run git and cargo commands'")
+                .conflicts_with("finish"))
+           .arg_from_usage("--print-config 'Print the configuration with the rendered templates and exit'")
+           .arg_from_usage("--offline 'Run without network access, the git push and the publishing are deferred till --finish'")
+           .arg(Arg::from_usage("--finish 'Complete the git push and the publishing of a release made with --offline'")
+                .conflicts_with_all(&["VERSION_KIND", "offline", "print-config"]))
+           .subcommand(SubCommand::with_name("config")
+                .about("Print the effective configuration, where each setting is coming from and exit")
+                .subcommand(SubCommand::with_name("check")
+                     .about("Check the configuration files and exit")))
+           .subcommand(SubCommand::with_name("notes")
+                .about("Print the release notes of a version from the changelog and exit")
+                .arg(Arg::with_name("VERSION")
+                     .help("The version of the release notes (default: the current version of the cargo project)")))
            .get_matches();
 
        let start_dir = matches.value_of("start-dir")
@@ -73,81 +296,247 @@ impl Config {
            return Err(format!("Invalid directory given to '--start-dir': '{}'!", start_dir.display()).into());
        }
 
-       let mut config = Config::from_file()?;
-       config.version_kind = value_t_or_exit!(matches.value_of("VERSION_KIND"), VersionKind);
-       config.start_dir = start_dir;
-
+       let mut cli_config = ConfigFromFile::default();
        if matches.is_present("no-cargo-publish") {
-           config.cargo_publish = ! matches.is_present("no-cargo-publish");
+           cli_config.cargo_publish = Some(false);
+           cli_config.sources.insert("cargo_publish".to_string(), Source::CommandLine("--no-cargo-publish"));
        }
 
        if matches.is_present("no-git-push") {
-           config.git_push = ! matches.is_present("no-git-push");
+           cli_config.git_push = Some(false);
+           cli_config.sources.insert("git_push".to_string(), Source::CommandLine("--no-git-push"));
+       }
+
+       let version_kind = if matches.is_present("VERSION_KIND") {
+           Some(value_t_or_exit!(matches.value_of("VERSION_KIND"), VersionKind))
+       } else {
+           None
+       };
+
+       let notes = match matches.subcommand_matches("notes") {
+           Some(notes_matches) => Some(notes_matches.value_of("VERSION")
+               .map(|v| Version::parse(v.trim_start_matches('v'))
+                   .map_err(|e| format!("Invalid version given to 'notes': '{}': {}", v, e)))
+               .transpose()?),
+
+           None => None
+       };
+
+       let config_matches = matches.subcommand_matches("config");
+       Ok(CommandArgs {
+           start_dir,
+           version_kind,
+           print_config: matches.is_present("print-config") || config_matches.map(|m| ! m.is_present("check")) == Some(true),
+           check_config: config_matches.map(|m| m.is_present("check")) == Some(true),
+           notes,
+           offline: matches.is_present("offline"),
+           finish: matches.is_present("finish"),
+           yes: matches.is_present("yes"),
+           verbose: matches.is_present("verbose"),
+           dry_run: matches.is_present("dry-run"),
+           cli_config
+       })
+   }
+}
+
+impl Config {
+   /// The configuration of `cargo_proj` from the configuration files
+   /// with the command line arguments `args` applied on top.
+   pub fn from_file_and_command_args(args: CommandArgs, cargo_proj: &CargoProj) -> RrResult<Config> {
+       let mut config = Config::from_file(args.cli_config, cargo_proj)?;
+       if let Some(version_kind) = args.version_kind {
+           config.version_kind = version_kind;
        }
 
+       config.print_config = args.print_config;
+       config.check_config = args.check_config;
+       config.notes = args.notes.map(|v| v.unwrap_or_else(|| cargo_proj.version().clone()));
+       config.offline = args.offline;
+       config.finish = args.finish;
+       config.yes = args.yes;
+       config.verbose = args.verbose;
+       config.dry_run = args.dry_run;
+
        config.check()?;
        Ok(config)
    }
 
-   pub fn commit_message(&self, proj: &CargoProj) -> String {
-       Template(&self.commit_message).render(proj)
+   pub fn commit_message(&self, vars: &TemplateVars) -> String {
+       Template(&self.commit_message).render(vars)
    }
 
-   pub fn tag_name(&self, proj: &CargoProj) -> String {
-       Template(&self.tag_name).render(proj)
+   pub fn tag_name(&self, vars: &TemplateVars) -> String {
+       Template(self.tag_name_template(vars)).render(vars)
    }
 
-   pub fn editor(&self) -> Command {
-       let editor_and_args = self.editor.split(' ').collect::<Vec<&str>>();
-       let mut cmd = Command::new(editor_and_args[0]);
-       let args = editor_and_args.iter().skip(1);
-       for arg in args {
-           cmd.arg(arg);
+   /// The configured template for the tag name, otherwise the default one, which
+   /// contains the project name if the project doesn't reside in the root directory
+   /// of the git repository, so that the tags of multiple projects don't collide.
+   fn tag_name_template(&self, vars: &TemplateVars) -> &str {
+       match self.tag_name {
+           Some(ref tag_name)                    => tag_name,
+           None if vars.crate_dir.is_empty()     => "v<NEW_VERSION>",
+           None                                  => "<PROJ_NAME>-v<NEW_VERSION>"
        }
+   }
+
+   pub fn tag_message(&self, vars: &TemplateVars) -> String {
+       Template(&self.tag_message).render(vars).trim_end().to_string()
+   }
 
-       cmd
+   pub fn dev_commit_message(&self, vars: &TemplateVars) -> String {
+       Template(&self.dev_commit_message).render(vars)
    }
 
-   fn from_file() -> RrResult<Config> {
-       let curr_file_config = ConfigFromFile::load_from_current_dir()?;
-       let home_file_config = ConfigFromFile::load_from_home_dir()?;
+   /// Prints the effective configuration - the templates rendered by `vars` -
+   /// and the source of each setting.
+   pub fn print(&self, vars: &TemplateVars) {
+       self.print_setting("cargo_publish", self.cargo_publish, None);
+       self.print_setting("git_push", self.git_push, None);
+       self.print_setting("registry", format!("{:?}", self.registry.as_deref().unwrap_or("")), None);
+       self.print_setting("commit_message", format!("{:?}", self.commit_message), Some(self.commit_message(vars)));
+       self.print_setting("tag_name", format!("{:?}", self.tag_name_template(vars)), Some(self.tag_name(vars)));
+       self.print_setting("tag_message", format!("{:?}", self.tag_message), Some(self.tag_message(vars)));
+       self.print_setting("sign_commit", self.sign_commit, None);
+       self.print_setting("sign_tag", self.sign_tag, None);
+       self.print_setting("dev_version", format!("{:?}", self.dev_version.as_ref().map(|k| k.to_string().to_lowercase()).unwrap_or_default()), None);
+       self.print_setting("dev_version_pre", format!("{:?}", self.dev_version_pre), None);
+       self.print_setting("dev_commit_message", format!("{:?}", self.dev_commit_message), Some(self.dev_commit_message(vars)));
+       self.print_setting("allow_untracked", self.allow_untracked, None);
+       self.print_setting("allow_unexpected_changes", self.allow_unexpected_changes, None);
+       self.print_setting("editor", format!("{:?}", self.editor), None);
+       self.print_setting("commit_log", self.commit_log, None);
+       self.print_setting("log_format", format!("{:?}", self.log_format), None);
+       self.print_setting("log_group", format!("{:?}", self.log_group.to_string().to_lowercase()), None);
+       self.print_setting("git_backend", format!("{:?}", self.git_backend.to_string().to_lowercase()), None);
+       self.print_setting("changelog", format!("{:?}", self.changelog.as_ref().map(|c| c.display().to_string()).unwrap_or_default()), None);
+       self.print_setting("changelog_required", self.changelog_required, None);
+   }
 
-       let file_config = match (curr_file_config, home_file_config) {
-           (Some(cfc), Some(hfc)) => cfc.combine(&hfc),
-           (Some(cfc), None)      => cfc,
-           (None     , Some(hfc)) => hfc,
-           (None     , None)      => ConfigFromFile::default()
-       };
+   fn print_setting<T: Display>(&self, name: &str, value: T, rendered: Option<String>) {
+       let source = self.sources.get(name).unwrap_or(&Source::Default);
+       println!("{:<50} # {}", format!("{} = {}", name, value), source);
+       if let Some(rendered) = rendered {
+           println!("    -> {:?}", rendered);
+       }
+   }
 
-       let config = config!(file_config, [
+   /// The editor command opening the `changelog` and - if given - the `log` file.
+   pub fn editor(&self, changelog: &Path, log: Option<&Path>) -> RrResult<Command> {
+       editor::command(&self.editor, changelog, log)
+   }
+
+   /// Reads the configuration files and the `Cargo.toml` metadata of `cargo_proj`
+   /// and applies the settings of `cli_config` on top.
+   ///
+   /// The settings are taken by precedence from: the command line flags, the
+   /// `RUSTY_RELEASE_*` environment variables, the configuration file of the cargo project
+   /// directory, the `[package.metadata.rusty-release]` table, the configuration files of the
+   /// parent directories - the nearest first -, the `[workspace.metadata.rusty-release]` table,
+   /// the home configuration file and the user configuration file.
+   fn from_file(cli_config: ConfigFromFile, cargo_proj: &CargoProj) -> RrResult<Config> {
+       let workspace_toml = cargo_proj.workspace_toml();
+
+       let mut configs = vec![cli_config];
+       configs.extend(ConfigFromFile::load_from_env()?);
+       configs.extend(ConfigFromFile::load_from_project_dir(cargo_proj.root_dir()?)?);
+       configs.extend(ConfigFromFile::load_from_metadata(cargo_proj.metadata(), cargo_proj.cargo_toml(),
+                                                         Source::PackageMetadata(cargo_proj.cargo_toml().to_path_buf()))?);
+       configs.extend(ConfigFromFile::load_from_parent_dirs(cargo_proj)?);
+       configs.extend(ConfigFromFile::load_from_metadata(cargo_proj.workspace_metadata(), workspace_toml,
+                                                         Source::WorkspaceMetadata(workspace_toml.to_path_buf()))?);
+       configs.extend(ConfigFromFile::load_from_home_dir()?);
+       configs.extend(ConfigFromFile::load_from_user_config_dir()?);
+
+       let file_config = configs.into_iter()
+           .fold(ConfigFromFile::default(), |config, other| config.combine(&other));
+
+       let mut config = config!(file_config, [
            cargo_publish,
            git_push,
            commit_message,
-           tag_name,
-           editor
+           tag_message,
+           sign_commit,
+           sign_tag,
+           dev_version_pre,
+           dev_commit_message,
+           allow_untracked,
+           allow_unexpected_changes,
+           editor,
+           commit_log,
+           log_format,
+           changelog_required
        ]);
 
+       config.tag_name = file_config.tag_name.clone();
+       config.registry = file_config.registry.clone();
+       config.changelog = file_config.changelog.as_ref().map(PathBuf::from);
+
+       if let Some(ref dev_version) = file_config.dev_version {
+           let source = file_config.sources.get("dev_version").unwrap_or(&Source::Default);
+           config.dev_version = Some(dev_version.parse::<VersionKind>()
+               .map_err(|e| format!("Invalid 'dev_version' from {}: {}", source, e))?);
+       }
+
+       if let Some(ref log_group) = file_config.log_group {
+           let source = file_config.sources.get("log_group").unwrap_or(&Source::Default);
+           config.log_group = log_group.parse::<LogGroup>()
+               .map_err(|e| format!("Invalid 'log_group' from {}: {}", source, e))?;
+       }
+
+       if let Some(ref git_backend) = file_config.git_backend {
+           let source = file_config.sources.get("git_backend").unwrap_or(&Source::Default);
+           config.git_backend = git_backend.parse::<GitBackend>()
+               .map_err(|e| format!("Invalid 'git_backend' from {}: {}", source, e))?;
+       }
+
+       config.sources.extend(file_config.sources);
        Ok(config)
    }
 
    fn default() -> RrResult<Config> {
-       Ok(Config {
+       let mut config = Config {
            version_kind: VersionKind::Patch,
-           start_dir: env::current_dir()?,
+           print_config: false,
+           check_config: false,
+           notes: None,
+           offline: false,
+           finish: false,
+           yes: false,
+           verbose: false,
+           dry_run: false,
            cargo_publish: true,
            git_push: true,
+           registry: None,
            commit_message: "<PROJ_NAME> <NEW_VERSION>".to_string(),
-           tag_name: "v<NEW_VERSION>".to_string(),
-           editor: {
-               if let Ok(editor) = env::var("EDITOR") {
-                   editor
-               } else if let Ok(visual) = env::var("VISUAL") {
-                   visual
-               } else {
-                   "gvim -o".to_string()
-               }
+           tag_name: None,
+           tag_message: "<PROJ_NAME> <NEW_VERSION>\n\n<CHANGELOG_SECTION>".to_string(),
+           sign_commit: false,
+           sign_tag: false,
+           dev_version: None,
+           dev_version_pre: "dev".to_string(),
+           dev_commit_message: "Start <PROJ_NAME> <NEW_VERSION> development".to_string(),
+           allow_untracked: false,
+           allow_unexpected_changes: false,
+           editor: "gvim -o".to_string(),
+           commit_log: true,
+           log_format: "<HASH> <SUBJECT> (<AUTHOR>, <DATE>) <REFS>".to_string(),
+           log_group: LogGroup::None,
+           git_backend: GitBackend::Cli,
+           changelog: None,
+           changelog_required: false,
+           sources: BTreeMap::new()
+       };
+
+       for var in &["EDITOR", "VISUAL"] {
+           if let Ok(editor) = env::var(var) {
+               config.editor = editor;
+               config.sources.insert("editor".to_string(), Source::Environment(var.to_string()));
+               break;
            }
-       })
+       }
+
+       Ok(config)
    }
 
    fn check(&self) -> RrResult<()> {
@@ -155,80 +544,313 @@ impl Config {
            return Err("Invalid, empty commit message!".into());
        }
 
-       if self.tag_name.is_empty() {
-           return Err("Invalid empty tag name!".into());
+       if let Some(ref tag_name) = self.tag_name {
+           if tag_name.is_empty() {
+               return Err("Invalid empty tag name!".into());
+           }
+
+           Template(tag_name).check()?;
        }
 
-       if self.editor.is_empty() {
-           return Err("Invalid, empty editor command!".into());
+       if self.tag_message.is_empty() {
+           return Err("Invalid, empty tag message!".into());
        }
 
-       Ok(())
-   }
-}
+       if self.dev_version == Some(VersionKind::Current) {
+           return Err("Invalid 'dev_version', the development version has to increment the released version!".into());
+       }
 
-/// Represents a string template that contains placeholders that can be replaced.
-/// Currently only the placeholders '<PROJ_NAME>' - representing the name of the
-/// cargo project and '<NEW_VERSION>' - representing the version of the release -
-/// are supported.
-#[derive(Debug)]
-struct Template<'a>(&'a str);
+       if self.dev_version_pre.is_empty() {
+           return Err("Invalid, empty pre-release part of the development version!".into());
+       }
 
-impl<'a> Template<'a> {
-    fn render(&self, proj: &CargoProj) -> String {
-        self.0.replace("<PROJ_NAME>", &proj.name().to_string())
-            .replace("<NEW_VERSION>", &proj.version().to_string())
-    }
+       if self.dev_commit_message.is_empty() {
+           return Err("Invalid, empty development commit message!".into());
+       }
+
+       Template(&self.commit_message).check()?;
+       Template(&self.tag_message).check()?;
+       Template(&self.dev_commit_message).check()?;
+
+       editor::parse(&self.editor)?;
+
+       if self.log_format.is_empty() {
+           return Err("Invalid, empty commit log format!".into());
+       }
+
+       Template(&self.log_format).check_placeholders(commit_log::PLACEHOLDERS)?;
+
+       if self.git_backend == GitBackend::Libgit2 {
+           for &(setting, sign) in &[("sign_commit", self.sign_commit), ("sign_tag", self.sign_tag)] {
+               if ! sign {
+                   continue;
+               }
+
+               let source = self.sources.get(setting).unwrap_or(&Source::Default);
+               let msg = format!("'{}' isn't supported by the git backend 'libgit2', set 'git_backend' to 'cli' or disable '{}'!",
+                                 setting, setting);
+
+               return Err(match source.path() {
+                   Some(path) => RrError::Config { path: path.to_path_buf(), line: None, msg },
+                   None       => format!("Invalid '{}' from {}: {}", setting, source, msg).into()
+               });
+           }
+       }
+
+       Ok(())
+   }
 }
 
 /// Represents the data from a `.rusty-release.toml` configuration file.
 #[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
 struct ConfigFromFile {
+    /// where the contained settings are coming from
+    #[serde(skip)]
+    sources: BTreeMap<String, Source>,
+
     cargo_publish: Option<bool>,
     git_push: Option<bool>,
+    registry: Option<String>,
     commit_message: Option<String>,
     tag_name: Option<String>,
-    editor: Option<String>
+    tag_message: Option<String>,
+    sign_commit: Option<bool>,
+    sign_tag: Option<bool>,
+    dev_version: Option<String>,
+    dev_version_pre: Option<String>,
+    dev_commit_message: Option<String>,
+    allow_untracked: Option<bool>,
+    allow_unexpected_changes: Option<bool>,
+    editor: Option<String>,
+    commit_log: Option<bool>,
+    log_format: Option<String>,
+    log_group: Option<String>,
+    git_backend: Option<String>,
+    changelog: Option<String>,
+    changelog_required: Option<bool>
 }
 
 impl ConfigFromFile {
-    fn load_from_current_dir() -> RrResult<Option<ConfigFromFile>> {
-        let path = env::current_dir()?.join(config_file_name());
-        if ! path.is_file() {
+    /// Loads the settings from the environment variables `RUSTY_RELEASE_<SETTING>`, where
+    /// `<SETTING>` is the upper case name of the setting, e.g. `RUSTY_RELEASE_SIGN_TAG=true`.
+    ///
+    /// Fails for variables of unknown settings - suggesting the closest known one - and for invalid values.
+    fn load_from_env() -> RrResult<Option<ConfigFromFile>> {
+        let mut settings = toml::value::Table::new();
+        let mut sources = BTreeMap::new();
+        for (var, value) in env::vars_os() {
+            let var = match var.into_string() {
+                Ok(ref var) if var.starts_with(ENV_PREFIX) => var.clone(),
+                _                                          => continue
+            };
+
+            let name = var[ENV_PREFIX.len()..].to_lowercase();
+            if ! SETTINGS.contains(&name.as_str()) {
+                let closest = SETTINGS.iter().min_by_key(|s| levenshtein(s, &name)).unwrap_or(&"");
+                return Err(format!("Unknown setting in environment variable '{}', did you mean '{}{}'?",
+                                   var, ENV_PREFIX, closest.to_uppercase()).into());
+            }
+
+            let value = value.into_string()
+                .map_err(|_| format!("Invalid, non unicode value of environment variable '{}'!", var))?;
+
+            let value = if BOOL_SETTINGS.contains(&name.as_str()) {
+                let bool_value = parse_bool(&value)
+                    .ok_or_else(|| format!("Invalid value '{}' of environment variable '{}', expected a boolean (true/false, yes/no, on/off or 1/0)!",
+                                           value, var))?;
+
+                toml::Value::Boolean(bool_value)
+            } else {
+                toml::Value::String(value)
+            };
+
+            settings.insert(name.clone(), value);
+            sources.insert(name, Source::Environment(var));
+        }
+
+        if settings.is_empty() {
             return Ok(None);
         }
 
-        Ok(Some(ConfigFromFile::load_from_file(&path)?))
+        let mut config: ConfigFromFile = toml::Value::Table(settings).try_into()?;
+        config.sources = sources;
+        Ok(Some(config))
+    }
+
+    /// Loads the configuration file in the directory `dir` of the cargo project or of one of its parents.
+    fn load_from_project_dir(dir: &Path) -> RrResult<Option<ConfigFromFile>> {
+        let path = dir.join(config_file_name());
+        if path.is_file() {
+            return Ok(Some(ConfigFromFile::load_from_file(&path, Source::ProjectFile(path.clone()))?));
+        }
+
+        Ok(None)
+    }
+
+    /// Loads the configuration files of the parent directories of the cargo project, continuing
+    /// upwards till the root directory of the git repository - or if the cargo project isn't in
+    /// a git repository, till the root directory of the workspace.
+    ///
+    /// The configurations are returned in the order of their precedence, the nearest first.
+    fn load_from_parent_dirs(cargo_proj: &CargoProj) -> RrResult<Vec<ConfigFromFile>> {
+        let proj_dir = cargo_proj.root_dir()?;
+        let last_dir = proj_dir.ancestors()
+            .find(|d| d.join(".git").exists())
+            .or_else(|| cargo_proj.workspace_toml().parent())
+            .unwrap_or(proj_dir);
+
+        let mut configs = Vec::new();
+        if proj_dir == last_dir {
+            return Ok(configs);
+        }
+
+        for dir in proj_dir.ancestors().skip(1) {
+            configs.extend(ConfigFromFile::load_from_project_dir(dir)?);
+            if dir == last_dir {
+                break;
+            }
+        }
+
+        Ok(configs)
     }
 
     fn load_from_home_dir() -> RrResult<Option<ConfigFromFile>> {
         if let Some(path) = dirs::home_dir().map(|d| d.join(config_file_name())) {
             if path.is_file() {
-                return Ok(Some(ConfigFromFile::load_from_file(&path)?));
+                return Ok(Some(ConfigFromFile::load_from_file(&path, Source::HomeFile(path.clone()))?));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Loads the configuration file `rusty-release/config.toml` in the user configuration
+    /// directory, which is `$XDG_CONFIG_HOME` or `~/.config` on Linux.
+    fn load_from_user_config_dir() -> RrResult<Option<ConfigFromFile>> {
+        if let Some(path) = dirs::config_dir().map(|d| d.join("rusty-release").join("config.toml")) {
+            if path.is_file() {
+                return Ok(Some(ConfigFromFile::load_from_file(&path, Source::UserConfigFile(path.clone()))?));
             }
         }
 
         Ok(None)
     }
 
-    fn load_from_file(path: &Path) -> RrResult<ConfigFromFile> {
+    /// Loads the `metadata` table of the `Cargo.toml` at `path`, all its settings are coming from `source`.
+    fn load_from_metadata(metadata: Option<&toml::Value>, path: &Path, source: Source) -> RrResult<Option<ConfigFromFile>> {
+        match metadata {
+            Some(metadata) => Ok(Some(ConfigFromFile::from_toml(metadata.clone(), None, path, source)?)),
+            None           => Ok(None)
+        }
+    }
+
+    /// Loads the configuration file at `path`, all its settings are coming from `source`.
+    fn load_from_file(path: &Path, source: Source) -> RrResult<ConfigFromFile> {
         map_file(path, |contents| {
-            let config = toml::from_str(&contents)?;
-            Ok(config)
+            let value = contents.parse::<toml::Value>()
+                .map_err(|e| RrError::Config {
+                    path: path.to_path_buf(),
+                    line: e.line_col().map(|(line, _)| line + 1),
+                    msg: e.to_string()
+                })?;
+
+            ConfigFromFile::from_toml(value, Some(&contents), path, source)
         })
     }
 
+    /// Reads the settings from the parsed `value` of the file at `path` with the
+    /// `contents`, which are used to locate errors. All settings are coming from `source`.
+    ///
+    /// Fails for unknown settings - suggesting the closest known one - and for invalid values.
+    fn from_toml(value: toml::Value, contents: Option<&str>, path: &Path, source: Source) -> RrResult<ConfigFromFile> {
+        let config_error = |name: Option<&str>, msg: String| {
+            let line = match (contents, name) {
+                (Some(contents), Some(name)) => setting_line(contents, name),
+                _                            => None
+            };
+
+            RrError::Config { path: path.to_path_buf(), line, msg }
+        };
+
+        let names = value.as_table()
+            .ok_or_else(|| config_error(None, "Expected a table of settings".to_string()))?
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for name in &names {
+            if ! SETTINGS.contains(&name.as_str()) {
+                let closest = SETTINGS.iter().min_by_key(|s| levenshtein(s, name)).unwrap_or(&"");
+                return Err(config_error(Some(name), format!("Unknown setting '{}', did you mean '{}'?", name, closest)));
+            }
+        }
+
+        let mut config: ConfigFromFile = value.try_into()
+            .map_err(|e: toml::de::Error| {
+                // errors of values only contain the name of the setting, but not the line
+                let msg = e.to_string();
+                let name = msg.split("for key `").nth(1).and_then(|rest| rest.split('`').next());
+                config_error(name, msg.clone())
+            })?;
+
+        for name in names {
+            config.sources.insert(name, source.clone());
+        }
+
+        Ok(config)
+    }
+
+    /// Combines the settings of both configurations, the settings of `self` win.
     fn combine(&self, other: &ConfigFromFile) -> ConfigFromFile {
+        let mut sources = other.sources.clone();
+        sources.extend(self.sources.clone());
+
         ConfigFromFile {
+            sources,
             cargo_publish: self.cargo_publish.or(other.cargo_publish),
             git_push: self.git_push.or(other.git_push),
+            registry: self.registry.as_ref().or(other.registry.as_ref()).cloned(),
             commit_message: self.commit_message.as_ref().or(other.commit_message.as_ref()).cloned(),
             tag_name: self.tag_name.as_ref().or(other.tag_name.as_ref()).cloned(),
-            editor: self.editor.as_ref().or(other.editor.as_ref()).cloned()
+            tag_message: self.tag_message.as_ref().or(other.tag_message.as_ref()).cloned(),
+            sign_commit: self.sign_commit.or(other.sign_commit),
+            sign_tag: self.sign_tag.or(other.sign_tag),
+            dev_version: self.dev_version.as_ref().or(other.dev_version.as_ref()).cloned(),
+            dev_version_pre: self.dev_version_pre.as_ref().or(other.dev_version_pre.as_ref()).cloned(),
+            dev_commit_message: self.dev_commit_message.as_ref().or(other.dev_commit_message.as_ref()).cloned(),
+            allow_untracked: self.allow_untracked.or(other.allow_untracked),
+            allow_unexpected_changes: self.allow_unexpected_changes.or(other.allow_unexpected_changes),
+            editor: self.editor.as_ref().or(other.editor.as_ref()).cloned(),
+            git_backend: self.git_backend.as_ref().or(other.git_backend.as_ref()).cloned(),
+            changelog: self.changelog.as_ref().or(other.changelog.as_ref()).cloned(),
+            commit_log: self.commit_log.or(other.commit_log),
+            log_format: self.log_format.as_ref().or(other.log_format.as_ref()).cloned(),
+            log_group: self.log_group.as_ref().or(other.log_group.as_ref()).cloned(),
+            changelog_required: self.changelog_required.or(other.changelog_required)
         }
     }
 }
 
+/// The line number of the setting `name` in the configuration file `contents`.
+fn setting_line(contents: &str, name: &str) -> Option<usize> {
+    contents.lines()
+        .position(|l| {
+            let mut key_and_value = l.splitn(2, '=');
+            key_and_value.next().map(|k| k.trim().trim_matches('"')) == Some(name) && key_and_value.next().is_some()
+        })
+        .map(|i| i + 1)
+}
+
+/// Parses the boolean `value` of an environment variable.
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1"  => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _                            => None
+    }
+}
+
 fn config_file_name() -> &'static str {
     ".rusty-release.toml"
 }
@@ -0,0 +1,70 @@
+use rr_result::RrResult;
+use git::Git;
+
+/// An author of the commits of a release.
+#[derive(Debug, PartialEq)]
+pub struct Contributor {
+    /// the name of the author, mapped by the '.mailmap'
+    pub name: String,
+
+    /// if the author hasn't contributed to any previous release
+    pub first_time: bool
+}
+
+/// The contributors of the release, which are the distinct authors of the commits since
+/// the previous release `prev_tag_name` - or of all commits for the first release - sorted
+/// by their names. If the cargo project resides in the sub directory `crate_dir` of the
+/// git repository, then only the commits touching it are considered.
+///
+/// Without a previous release nobody is flagged as first-time contributor.
+pub fn find(git: &dyn Git, prev_tag_name: Option<&str>, crate_dir: &str) -> RrResult<Vec<Contributor>> {
+    let path = if crate_dir.is_empty() { None } else { Some(".") };
+    let prev_authors = match prev_tag_name {
+        Some(prev_tag_name) => git.authors(prev_tag_name, path)?,
+        None                => Vec::new()
+    };
+
+    let mut contributors: Vec<Contributor> = Vec::new();
+    for commit in git.commits("HEAD", prev_tag_name, path)? {
+        if ! contributors.iter().any(|c| c.name == commit.author) {
+            contributors.push(Contributor {
+                first_time: prev_tag_name.is_some() && ! prev_authors.contains(&commit.author),
+                name: commit.author
+            });
+        }
+    }
+
+    contributors.sort_by_key(|c| c.name.to_lowercase());
+    Ok(contributors)
+}
+
+/// Formats the `contributors` as comma separated list, e.g. 'Alice, Bob (first contribution)'.
+pub fn format(contributors: &[Contributor]) -> String {
+    contributors.iter()
+        .map(|c| if c.first_time { format!("{} (first contribution)", c.name) } else { c.name.clone() })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use runner::ScriptedRunner;
+    use git::GitCli;
+
+    #[test]
+    fn flags_first_time_contributors() {
+        let log = "\x1eaaa\x1fbob\x1f2019-02-24\x1fFix bar\x1f\x1f\n\
+                   \x1ebbb\x1fAlice\x1f2019-02-23\x1fAdd foo\x1f\x1f\n\
+                   \x1eccc\x1fbob\x1f2019-02-22\x1fAdd bar\x1f\x1f\n";
+
+        let runner = ScriptedRunner::new()
+            .expect(&["git", "--no-pager", "log", "--format=%aN", "v0.1.0"], 0, "Alice\n")
+            .expect(&["git", "--no-pager", "log", "--date=short", "--name-only", "--format=%x1e%h%x1f%aN%x1f%ad%x1f%s%x1f%b%x1f",
+                      "v0.1.0..HEAD"], 0, log);
+
+        let contributors = find(&GitCli::new(&runner), Some("v0.1.0"), "").unwrap();
+        runner.assert_done();
+        assert_eq!(format(&contributors), "Alice, bob (first contribution)");
+    }
+}
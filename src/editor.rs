@@ -0,0 +1,122 @@
+use std::path::Path;
+use std::process::Command;
+use shell_words;
+use rr_result::RrResult;
+
+/// The placeholder of the editor command for the path of the changelog.
+const CHANGELOG: &str = "{changelog}";
+
+/// The placeholder of the editor command for the path of the file
+/// containing the commits since the previous release.
+const LOG: &str = "{log}";
+
+/// GUI editors returning immediately - by forking into the background or by handing
+/// the files over to an already running instance - and the flags letting them wait
+/// till the files are closed. The first flag is added if none of them is given.
+const WAIT_FLAGS: &[(&str, &[&str])] = &[
+    ("code",          &["--wait", "-w"]),
+    ("code-insiders", &["--wait", "-w"]),
+    ("codium",        &["--wait", "-w"]),
+    ("subl",          &["--wait", "-w"]),
+    ("atom",          &["--wait", "-w"]),
+    ("zed",           &["--wait", "-w"]),
+    ("mate",          &["--wait", "-w"]),
+    ("gedit",         &["--wait", "-w"]),
+    ("kate",          &["--block", "-b"]),
+    ("gvim",          &["--nofork", "-f"]),
+    ("mvim",          &["--nofork", "-f"])
+];
+
+/// Splits the `editor` command into its words like a shell does, so that
+/// quoted arguments and paths containing spaces are kept together.
+pub fn parse(editor: &str) -> RrResult<Vec<String>> {
+    let words = shell_words::split(editor)
+        .map_err(|e| format!("Invalid editor command '{}': {}", editor, e))?;
+
+    if words.is_empty() {
+        return Err("Invalid, empty editor command!".into());
+    }
+
+    Ok(words)
+}
+
+/// The `editor` command opening the `changelog` and - if given - the `log` file.
+///
+/// The placeholders `{changelog}` and `{log}` in the editor command are replaced by the
+/// paths of the files - without a `log` file the arguments containing `{log}` are left
+/// out - and if the editor command contains none of them, then the paths are appended.
+pub fn command(editor: &str, changelog: &Path, log: Option<&Path>) -> RrResult<Command> {
+    let argv = argv(editor, changelog, log)?;
+    let mut cmd = Command::new(&argv[0]);
+    cmd.args(&argv[1..]);
+    Ok(cmd)
+}
+
+fn argv(editor: &str, changelog: &Path, log: Option<&Path>) -> RrResult<Vec<String>> {
+    let mut words = parse(editor)?;
+    let changelog = changelog.to_string_lossy();
+    let log = log.map(|l| l.to_string_lossy());
+
+    if words.iter().any(|w| w.contains(CHANGELOG) || w.contains(LOG)) {
+        words = words.into_iter()
+            .filter_map(|w| match log {
+                Some(ref log)           => Some(w.replace(LOG, log)),
+                None if w.contains(LOG) => None,
+                None                    => Some(w)
+            })
+            .map(|w| w.replace(CHANGELOG, &changelog))
+            .collect();
+    } else {
+        words.push(changelog.into_owned());
+        words.extend(log.map(|l| l.into_owned()));
+    }
+
+    let program = Path::new(&words[0]).file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    if let Some(&(_, flags)) = WAIT_FLAGS.iter().find(|&&(name, _)| name == program) {
+        if ! words[1..].iter().any(|w| flags.contains(&w.as_str())) {
+            words.insert(1, flags[0].to_string());
+        }
+    }
+
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argv_of(editor: &str, log: Option<&str>) -> Vec<String> {
+        argv(editor, Path::new("/my project/CHANGELOG"), log.map(Path::new)).unwrap()
+    }
+
+    #[test]
+    fn appends_files_without_placeholders() {
+        assert_eq!(argv_of("vim -o", Some("/tmp/log")), ["vim", "-o", "/my project/CHANGELOG", "/tmp/log"]);
+        assert_eq!(argv_of("vim -o", None), ["vim", "-o", "/my project/CHANGELOG"]);
+    }
+
+    #[test]
+    fn keeps_quoted_arguments_together() {
+        assert_eq!(argv_of("'/opt/my editor/bin/ed' --title \"Release notes\"", None),
+                   ["/opt/my editor/bin/ed", "--title", "Release notes", "/my project/CHANGELOG"]);
+
+        assert!(parse("vim 'unterminated").is_err());
+        assert!(parse("  ").is_err());
+    }
+
+    #[test]
+    fn replaces_placeholders() {
+        assert_eq!(argv_of("emacs {log} --file={changelog}", Some("/tmp/log")), ["emacs", "/tmp/log", "--file=/my project/CHANGELOG"]);
+        assert_eq!(argv_of("emacs {log} --file={changelog}", None), ["emacs", "--file=/my project/CHANGELOG"]);
+    }
+
+    #[test]
+    fn adds_wait_flags_of_gui_editors() {
+        assert_eq!(argv_of("code", None), ["code", "--wait", "/my project/CHANGELOG"]);
+        assert_eq!(argv_of("/usr/bin/gvim -o", None), ["/usr/bin/gvim", "--nofork", "-o", "/my project/CHANGELOG"]);
+        assert_eq!(argv_of("subl -w", None), ["subl", "-w", "/my project/CHANGELOG"]);
+    }
+}
@@ -1,184 +1,509 @@
-use std::io::Write;
-use std::process::Command;
-use tempfile::{NamedTempFile, Builder};
+use std::path::{Path, PathBuf};
 use rr_result::RrResult;
-use utils::check_output;
+use runner::{Cmd, CmdOutput, CommandRunner};
+use utils::file_list;
 
-/// Checks if git has a clean state, a non dirty working directory,
-/// an empty stage area and that the remote repository isn't ahead of
-/// the local one.
-pub fn check_state() -> RrResult<()> {
-    if has_dirty_working_dir()? {
-        return Err("Can't operate with dirty git working directory! Clear or commit changes!".into());
-    }
+#[cfg(feature = "libgit2")]
+use libgit2::Libgit2;
 
-    if has_staged_changes()? {
-        return Err("Can't operate with non empty git staging area! Clear or commit staged changes!".into());
-    }
+pub type CommitHash = String;
 
-    let local_head = local_head()?;
+/// A commit of the log.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LogEntry {
+    /// the abbreviated hash of the commit
+    pub hash: CommitHash,
 
-    remote_update()?;
-    let remote_head = remote_head()?;
+    /// the name of the author, mapped by the '.mailmap'
+    pub author: String,
 
-    let merge_base = merge_base(&local_head, &remote_head)?;
-    if remote_head != merge_base {
-        return Err("Can't operate with diverging local and remote git repository! Synchronize them!".into())
-    }
+    /// the date of the authorship, e.g. '2019-02-24'
+    pub date: String,
 
-    Ok(())
-}
+    /// the first line of the commit message
+    pub subject: String,
 
-pub fn add_update() -> RrResult<()> {
-    let output = Command::new("git")
-        .arg("add")
-        .arg("--update")
-        .output()?;
+    /// the commit message after the subject
+    pub body: String,
 
-    check_output(&output)?;
-    Ok(())
+    /// the paths - relative to the root directory of the repository - changed by the
+    /// commit compared to its parent, empty for merge commits
+    pub paths: Vec<String>
 }
 
-pub fn commit(msg: &str) -> RrResult<()> {
-    let output = Command::new("git")
-        .arg("commit")
-        .arg(format!("--message={}", msg))
-        .output()?;
+// The implementation of the git operations
+arg_enum! {
+    #[derive(Eq, PartialEq, Debug, Clone, Copy)]
+    pub enum GitBackend {
+        // runs the git command line tool
+        Cli,
 
-    check_output(&output)?;
-    Ok(())
+        // uses the libgit2 library, only available with the cargo feature 'libgit2'
+        Libgit2
+    }
 }
 
-pub fn tag(name: &str) -> RrResult<()> {
-    let output = Command::new("git")
-        .arg("tag")
-        .arg(name)
-        .output()?;
+/// Opens the git repository of the current directory with `backend`,
+/// the `Cli` backend runs the git commands by `runner`.
+pub fn open<'a>(backend: GitBackend, runner: &'a dyn CommandRunner) -> RrResult<Box<dyn Git + 'a>> {
+    match backend {
+        GitBackend::Cli => Ok(Box::new(GitCli::new(runner))),
+
+        #[cfg(feature = "libgit2")]
+        GitBackend::Libgit2 => Ok(Box::new(Libgit2::open()?)),
 
-    check_output(&output)?;
-    Ok(())
+        #[cfg(not(feature = "libgit2"))]
+        GitBackend::Libgit2 => Err("The git backend 'libgit2' isn't available, rusty-release has to be built with the cargo feature 'libgit2'!".into())
+    }
 }
 
-pub fn push() -> RrResult<()> {
-    let output = Command::new("git")
-        .arg("push")
-        .output()?;
+/// The git operations needed for a release.
+///
+/// Has to be `Sync`, because the checks of the release run in parallel.
+pub trait Git: Sync {
+    /// Checks if git has a clean state, a non dirty working directory,
+    /// an empty stage area and no untracked files - unless `allow_untracked`.
+    fn check_state(&self, allow_untracked: bool) -> RrResult<()> {
+        if self.has_dirty_working_dir()? {
+            return Err("Can't operate with dirty git working directory! Clear or commit changes!".into());
+        }
+
+        if self.has_staged_changes()? {
+            return Err("Can't operate with non empty git staging area! Clear or commit staged changes!".into());
+        }
+
+        let untracked = self.untracked_files()?;
+        if ! untracked.is_empty() {
+            if ! allow_untracked {
+                return Err(format!("Can't operate with untracked files! Add, ignore or remove them:\n{}", file_list(&untracked)).into());
+            }
+
+            warnln!("Untracked files, which aren't part of the release:\n{}", file_list(&untracked));
+        }
+
+        Ok(())
+    }
 
-    check_output(&output)?;
+    /// Checks that the remote repository isn't ahead of the local one.
+    fn check_remote(&self) -> RrResult<()> {
+        let local_head = self.local_head()?;
 
-    let output = Command::new("git")
-        .arg("push")
-        .arg("--tags")
-        .output()?;
+        self.remote_update()?;
+        let remote_head = self.remote_head()?;
 
-    check_output(&output)?;
-    Ok(())
-}
+        let merge_base = self.merge_base(&local_head, &remote_head)?;
+        if remote_head != merge_base {
+            return Err("Can't operate with diverging local and remote git repository! Synchronize them!".into())
+        }
+
+        Ok(())
+    }
+
+    /// Stages the `files`.
+    fn add(&self, files: &[PathBuf]) -> RrResult<()>;
+
+    /// The tracked files with uncommited changes in the working directory.
+    fn modified_files(&self) -> RrResult<Vec<PathBuf>>;
+
+    /// Commits the staged changes, the commit is signed if `sign` is true.
+    fn commit(&self, msg: &str, sign: bool) -> RrResult<()>;
+
+    /// Creates an annotated tag, which is also signed if `sign` is true.
+    fn tag(&self, name: &str, msg: &str, sign: bool) -> RrResult<()>;
+
+    /// Pushes the current branch to its upstream branch and all tags.
+    fn push(&self) -> RrResult<()>;
+
+    /// Checks out the files of `rev` into the new directory `dir` - without changing the working
+    /// directory or the index of the repository - e.g. to publish a release from its tag.
+    fn checkout_into(&self, rev: &str, dir: &Path) -> RrResult<()>;
+
+    /// Removes the directory `dir` created by `checkout_into`.
+    fn remove_checkout(&self, dir: &Path) -> RrResult<()>;
+
+    /// The commits reachable from `from`, but not from `to` - or all commits reachable from `from`
+    /// if `to` is `None` - newest first. If `path` is given, then only the commits touching `path`
+    /// are considered.
+    fn commits(&self, from: &str, to: Option<&str>, path: Option<&str>) -> RrResult<Vec<LogEntry>>;
+
+    /// The distinct names - mapped by the '.mailmap' - of the authors of the commits reachable
+    /// from `rev`, newest first. If `path` is given, then only the commits touching `path` are considered.
+    fn authors(&self, rev: &str, path: Option<&str>) -> RrResult<Vec<String>>;
+
+    /// The name of the currently checked out branch.
+    fn current_branch(&self) -> RrResult<String>;
+
+    /// The upstream branch of the current branch, e.g. 'origin/master'.
+    fn upstream(&self) -> RrResult<String>;
+
+    /// The root directory of the git repository.
+    fn root_dir(&self) -> RrResult<PathBuf>;
+
+    /// The git directory of the repository, usually '.git' in the root directory.
+    fn git_dir(&self) -> RrResult<PathBuf>;
+
+    /// The path of the current directory relative to the root directory of
+    /// the git repository, which is empty for the root directory itself.
+    fn path_in_repo(&self) -> RrResult<String>;
+
+    fn has_tag(&self, name: &str) -> RrResult<bool>;
 
-pub fn log_file(from: &str, to: Option<&str>) -> RrResult<NamedTempFile> {
-    let output = log(from, to)?;
+    /// The names of the tags merged into HEAD, so that tags of other branches are ignored.
+    fn merged_tags(&self) -> RrResult<Vec<String>>;
 
-    let prefix = if let Some(to) = to {
-        format!("{}...{}___", from, to)
-    } else {
-        from.to_owned()
-    };
+    /// If a key for signing commits and tags is configured in git or - if none is
+    /// configured - if gpg has a secret key for the email of the committer.
+    fn has_signing_key(&self) -> RrResult<bool>;
 
-    let mut log_file = Builder::new()
-        .prefix(&prefix)
-        .tempfile()?;
+    /// If the working directory has uncommited changes.
+    fn has_dirty_working_dir(&self) -> RrResult<bool>;
 
-    log_file.write_all(output.as_bytes())?;
-    Ok(log_file)
+    /// If the stage area contains uncommited changes.
+    fn has_staged_changes(&self) -> RrResult<bool>;
+
+    /// The files neither tracked nor ignored by git.
+    fn untracked_files(&self) -> RrResult<Vec<PathBuf>>;
+
+    /// Update the local refs to the remote repository.
+    fn remote_update(&self) -> RrResult<()>;
+
+    /// The commit of HEAD.
+    fn local_head(&self) -> RrResult<CommitHash>;
+
+    /// The commit `rev` - e.g. a tag - is pointing to.
+    fn commit_of(&self, rev: &str) -> RrResult<CommitHash>;
+
+    /// The commit of the upstream branch of the current branch.
+    fn remote_head(&self) -> RrResult<CommitHash>;
+
+    fn merge_base(&self, commit1: &str, commit2: &str) -> RrResult<CommitHash>;
 }
 
-pub fn log(from: &str, to: Option<&str>) -> RrResult<String> {
-    let output = Command::new("git")
-        .arg("--no-pager")
-        .arg("log")
-        .arg("--decorate=short")
-        .arg("--pretty=oneline")
-        .arg("--abbrev-commit")
-        .arg(if let Some(to) = to { format!("{}...{}", from, to) } else { from.to_owned() })
-        .output()?;
-
-    check_output(&output)?;
-    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+/// Runs the git command line tool by a `CommandRunner`.
+pub struct GitCli<'a> {
+    runner: &'a dyn CommandRunner
 }
 
-pub fn has_tag(name: &str) -> RrResult<bool> {
-    let output = Command::new("git")
-        .arg("rev-parse")
-        .arg("--verify")
-        .arg("--quiet")
-        .arg(name)
-        .output()?;
+impl<'a> GitCli<'a> {
+    pub fn new(runner: &'a dyn CommandRunner) -> GitCli<'a> {
+        GitCli { runner }
+    }
 
-    Ok(output.status.success())
-}
+    fn commit_hash(&self, refname: &str) -> RrResult<CommitHash> {
+        self.run_trimmed(&["rev-parse", refname])
+    }
 
-/// If the working directory has uncommited changes.
-pub fn has_dirty_working_dir() -> RrResult<bool> {
-    let output = Command::new("git")
-        .arg("diff-files")
-        .arg("--quiet")
-        .arg("--exit-code")
-        .output()?;
+    fn run(&self, cmd: Cmd) -> RrResult<CmdOutput> {
+        self.runner.run(&cmd)
+    }
 
-    Ok(output.status.code() == Some(1))
-}
+    fn run_checked(&self, cmd: Cmd) -> RrResult<CmdOutput> {
+        self.run(cmd)?.check()
+    }
 
-/// If the stage area contains uncommited changes.
-fn has_staged_changes() -> RrResult<bool> {
-    let output = Command::new("git")
-        .arg("diff-index")
-        .arg("--quiet")
-        .arg("--exit-code")
-        .arg("--cached HEAD")
-        .output()?;
+    /// The value of the git config `name`, `None` if it isn't set.
+    fn config_value(&self, name: &str) -> RrResult<Option<String>> {
+        let output = self.run(git().args(["config", "--get", name]))?;
+        Ok(Some(output.stdout_trimmed()).filter(|v| output.success() && ! v.is_empty()).map(str::to_string))
+    }
 
-    Ok(output.status.code() == Some(1))
+    /// Runs git with `args` and returns its trimmed output.
+    fn run_trimmed(&self, args: &[&str]) -> RrResult<String> {
+        Ok(self.run_checked(git().args(args))?.stdout_trimmed().to_string())
+    }
 }
 
-/// Update the local refs to the remote repository.
-fn remote_update() -> RrResult<()> {
-    let output = Command::new("git")
-        .arg("remote")
-        .arg("update")
-        .output()?;
+impl<'a> Git for GitCli<'a> {
+    fn add(&self, files: &[PathBuf]) -> RrResult<()> {
+        self.run_checked(git().arg("add").arg("--").args(files))?;
+        Ok(())
+    }
 
-    check_output(&output)?;
-    Ok(())
-}
+    fn modified_files(&self) -> RrResult<Vec<PathBuf>> {
+        let root_dir = self.root_dir()?;
+        let output = self.run_checked(git().args(["diff-files", "--name-only", "-z"]))?;
+        Ok(output.stdout
+           .split('\0')
+           .filter(|f| ! f.is_empty())
+           .map(|f| root_dir.join(f))
+           .collect())
+    }
+
+    fn commit(&self, msg: &str, sign: bool) -> RrResult<()> {
+        let mut cmd = git().arg("commit");
+        if sign {
+            cmd = cmd.arg("--gpg-sign");
+        }
+
+        self.run_checked(cmd.arg(format!("--message={}", msg)))?;
+        Ok(())
+    }
+
+    fn tag(&self, name: &str, msg: &str, sign: bool) -> RrResult<()> {
+        self.run_checked(git()
+            .arg("tag")
+            .arg(if sign { "--sign" } else { "--annotate" })
+            // keep Markdown headings of the changelog section, which would be stripped as comments
+            .arg("--cleanup=whitespace")
+            .arg(format!("--message={}", msg))
+            .arg(name))?;
+
+        Ok(())
+    }
 
-type CommitHash = String;
+    fn push(&self) -> RrResult<()> {
+        self.run_checked(git().arg("push"))?;
+        self.run_checked(git().args(["push", "--tags"]))?;
+        Ok(())
+    }
 
-fn local_head() -> RrResult<CommitHash> {
-    commit_hash("@")
+    fn checkout_into(&self, rev: &str, dir: &Path) -> RrResult<()> {
+        self.run_checked(git().args(["worktree", "add", "--quiet", "--detach"]).arg(dir).arg(rev))?;
+        Ok(())
+    }
+
+    fn remove_checkout(&self, dir: &Path) -> RrResult<()> {
+        self.run_checked(git().args(["worktree", "remove", "--force"]).arg(dir))?;
+        Ok(())
+    }
+
+    fn commits(&self, from: &str, to: Option<&str>, path: Option<&str>) -> RrResult<Vec<LogEntry>> {
+        // each commit starts with a record separator and its fields are separated by unit
+        // separators, the changed paths listed by '--name-only' follow after the last one
+        let mut cmd = git()
+            .args(["--no-pager", "log", "--date=short", "--name-only", "--format=%x1e%h%x1f%aN%x1f%ad%x1f%s%x1f%b%x1f"])
+            .arg(if let Some(to) = to { format!("{}..{}", to, from) } else { from.to_owned() });
+
+        if let Some(path) = path {
+            cmd = cmd.arg("--").arg(path);
+        }
+
+        let output = self.run_checked(cmd)?;
+        output.stdout
+            .split('\x1e')
+            .skip(1)
+            .map(|record| {
+                let fields = record.split('\x1f').collect::<Vec<_>>();
+                if fields.len() != 6 {
+                    return Err(format!("Unexpected output of git log: {:?}", record).into());
+                }
+
+                Ok(LogEntry {
+                    hash: fields[0].to_string(),
+                    author: fields[1].to_string(),
+                    date: fields[2].to_string(),
+                    subject: fields[3].to_string(),
+                    body: fields[4].trim().to_string(),
+                    paths: fields[5].lines().filter(|l| ! l.is_empty()).map(str::to_string).collect()
+                })
+            })
+            .collect()
+    }
+
+    fn authors(&self, rev: &str, path: Option<&str>) -> RrResult<Vec<String>> {
+        let mut cmd = git().args(["--no-pager", "log", "--format=%aN", rev]);
+        if let Some(path) = path {
+            cmd = cmd.arg("--").arg(path);
+        }
+
+        let mut authors = Vec::new();
+        for author in self.run_checked(cmd)?.stdout.lines() {
+            if ! authors.iter().any(|a| a == author) {
+                authors.push(author.to_string());
+            }
+        }
+
+        Ok(authors)
+    }
+
+    fn current_branch(&self) -> RrResult<String> {
+        self.run_trimmed(&["rev-parse", "--abbrev-ref", "HEAD"])
+    }
+
+    fn upstream(&self) -> RrResult<String> {
+        self.run_trimmed(&["rev-parse", "--abbrev-ref", "@{u}"])
+    }
+
+    fn root_dir(&self) -> RrResult<PathBuf> {
+        Ok(PathBuf::from(self.run_trimmed(&["rev-parse", "--show-toplevel"])?))
+    }
+
+    fn git_dir(&self) -> RrResult<PathBuf> {
+        Ok(PathBuf::from(self.run_trimmed(&["rev-parse", "--absolute-git-dir"])?))
+    }
+
+    fn path_in_repo(&self) -> RrResult<String> {
+        Ok(self.run_trimmed(&["rev-parse", "--show-prefix"])?.trim_end_matches('/').to_string())
+    }
+
+    fn has_tag(&self, name: &str) -> RrResult<bool> {
+        Ok(self.run(git().args(["rev-parse", "--verify", "--quiet", name]))?.success())
+    }
+
+    fn merged_tags(&self) -> RrResult<Vec<String>> {
+        Ok(self.run_checked(git().args(["tag", "--list", "--merged", "HEAD"]))?.stdout.lines().map(str::to_string).collect())
+    }
+
+    fn has_signing_key(&self) -> RrResult<bool> {
+        if self.config_value("user.signingkey")?.is_some() {
+            return Ok(true);
+        }
+
+        // without a configured key git lets gpg - or gpgsm for x509 - pick the key by the
+        // email of the committer, but ssh signing always needs a configured key
+        let program = match self.config_value("gpg.format")?.as_deref() {
+            None | Some("openpgp") => self.config_value("gpg.openpgp.program")?
+                .or(self.config_value("gpg.program")?)
+                .unwrap_or("gpg".to_string()),
+            Some("x509") => self.config_value("gpg.x509.program")?.unwrap_or("gpgsm".to_string()),
+            _ => return Ok(false)
+        };
+
+        // the ident has the format 'Name <email> timestamp timezone'
+        let ident = self.run_trimmed(&["var", "GIT_COMMITTER_IDENT"])?;
+        let email = match (ident.find('<'), ident.rfind('>')) {
+            (Some(start), Some(end)) if start < end => &ident[start + 1..end],
+            _ => return Ok(false)
+        };
+
+        // a missing gpg just means that there's no key
+        Ok(self.run(Cmd::new(program).arg("--list-secret-keys").arg(email)).map(|o| o.success()).unwrap_or(false))
+    }
+
+    fn has_dirty_working_dir(&self) -> RrResult<bool> {
+        Ok(self.run(git().args(["diff-files", "--quiet", "--exit-code"]))?.code == Some(1))
+    }
+
+    fn has_staged_changes(&self) -> RrResult<bool> {
+        Ok(self.run(git().args(["diff-index", "--quiet", "--exit-code", "--cached", "HEAD"]))?.code == Some(1))
+    }
+
+    fn untracked_files(&self) -> RrResult<Vec<PathBuf>> {
+        let root_dir = self.root_dir()?;
+        let output = self.run_checked(git().args(["ls-files", "--others", "--exclude-standard", "--full-name", "-z", "--", ":/"]))?;
+        Ok(output.stdout
+           .split('\0')
+           .filter(|f| ! f.is_empty())
+           .map(|f| root_dir.join(f))
+           .collect())
+    }
+
+    fn remote_update(&self) -> RrResult<()> {
+        self.run_checked(git().args(["remote", "update"]))?;
+        Ok(())
+    }
+
+    fn local_head(&self) -> RrResult<CommitHash> {
+        self.commit_hash("@")
+    }
+
+    fn commit_of(&self, rev: &str) -> RrResult<CommitHash> {
+        self.commit_hash(&format!("{}^{{commit}}", rev))
+    }
+
+    fn remote_head(&self) -> RrResult<CommitHash> {
+        self.commit_hash("@{u}")
+    }
+
+    fn merge_base(&self, commit1: &str, commit2: &str) -> RrResult<CommitHash> {
+        self.run_trimmed(&["merge-base", commit1, commit2])
+    }
 }
 
-fn remote_head() -> RrResult<CommitHash> {
-    commit_hash("@{u}")
+fn git() -> Cmd {
+    Cmd::new("git")
 }
 
-fn commit_hash(refname: &str) -> RrResult<CommitHash> {
-    let output = Command::new("git")
-        .arg("rev-parse")
-        .arg(refname)
-        .output()?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use runner::{RecordingRunner, ScriptedRunner};
+
+    #[test]
+    fn signed_commit_and_tag() {
+        let runner = RecordingRunner::fake();
+        let git = GitCli::new(&runner);
+        git.commit("foo 0.1.1", true).unwrap();
+        git.tag("v0.1.1", "foo 0.1.1", true).unwrap();
+
+        assert_eq!(runner.argvs(), [
+            vec!["git", "commit", "--gpg-sign", "--message=foo 0.1.1"],
+            vec!["git", "tag", "--sign", "--cleanup=whitespace", "--message=foo 0.1.1", "v0.1.1"]
+        ]);
+    }
+
+    #[test]
+    fn signing_key_of_committer_email() {
+        let script = |has_key| ScriptedRunner::new()
+            .expect(&["git", "config", "--get", "user.signingkey"], 1, "")
+            .expect(&["git", "config", "--get", "gpg.format"], 1, "")
+            .expect(&["git", "config", "--get", "gpg.openpgp.program"], 1, "")
+            .expect(&["git", "config", "--get", "gpg.program"], 0, "gpg2\n")
+            .expect(&["git", "var", "GIT_COMMITTER_IDENT"], 0, "Alice <alice@example.com> 1550000000 +0100\n")
+            .expect(&["gpg2", "--list-secret-keys", "alice@example.com"], if has_key { 0 } else { 2 }, "");
+
+        assert!(GitCli::new(&script(true)).has_signing_key().unwrap());
+        assert!(! GitCli::new(&script(false)).has_signing_key().unwrap());
+
+        let runner = ScriptedRunner::new()
+            .expect(&["git", "config", "--get", "user.signingkey"], 1, "")
+            .expect(&["git", "config", "--get", "gpg.format"], 0, "ssh\n");
+
+        assert!(! GitCli::new(&runner).has_signing_key().unwrap());
+        runner.assert_done();
+    }
 
-    check_output(&output)?;
-    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
-}
+    #[test]
+    fn parses_commits_of_log() {
+        let log = "\x1eaaa\x1fAlice\x1f2019-02-24\x1fAdd foo\x1fLonger\ndescription\n\x1f\n\nsrc/foo.rs\nREADME.md\n\
+                   \x1ebbb\x1fBob\x1f2019-02-23\x1fMerge pull request #7 from bob/bar\x1f\x1f\n";
+
+        let runner = ScriptedRunner::new()
+            .expect(&["git", "--no-pager", "log", "--date=short", "--name-only", "--format=%x1e%h%x1f%aN%x1f%ad%x1f%s%x1f%b%x1f",
+                      "v0.1.0..HEAD", "--", "."], 0, log);
+
+        let commits = GitCli::new(&runner).commits("HEAD", Some("v0.1.0"), Some(".")).unwrap();
+        assert_eq!(commits, [
+            LogEntry {
+                hash: "aaa".to_string(),
+                author: "Alice".to_string(),
+                date: "2019-02-24".to_string(),
+                subject: "Add foo".to_string(),
+                body: "Longer\ndescription".to_string(),
+                paths: vec!["src/foo.rs".to_string(), "README.md".to_string()]
+            },
+            LogEntry {
+                hash: "bbb".to_string(),
+                author: "Bob".to_string(),
+                date: "2019-02-23".to_string(),
+                subject: "Merge pull request #7 from bob/bar".to_string(),
+                body: String::new(),
+                paths: Vec::new()
+            }
+        ]);
+    }
 
-fn merge_base(refname1: &str, refname2: &str) -> RrResult<CommitHash> {
-    let output = Command::new("git")
-        .arg("merge-base")
-        .arg(refname1)
-        .arg(refname2)
-        .output()?;
+    #[test]
+    fn diverging_remote_fails_check() {
+        let runner = ScriptedRunner::new()
+            .expect(&["git", "rev-parse", "@"], 0, "aaa\n")
+            .expect(&["git", "remote", "update"], 0, "")
+            .expect(&["git", "rev-parse", "@{u}"], 0, "bbb\n")
+            .expect(&["git", "merge-base", "aaa", "bbb"], 0, "ccc\n");
 
-    check_output(&output)?;
-    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
-}
+        assert!(GitCli::new(&runner).check_remote().is_err());
+        runner.assert_done();
+    }
 
+    #[test]
+    fn untracked_files_fail_check_unless_allowed() {
+        let script = || ScriptedRunner::new()
+            .expect(&["git", "diff-files", "--quiet", "--exit-code"], 0, "")
+            .expect(&["git", "diff-index", "--quiet", "--exit-code", "--cached", "HEAD"], 0, "")
+            .expect(&["git", "rev-parse", "--show-toplevel"], 0, "/repo\n")
+            .expect(&["git", "ls-files", "--others", "--exclude-standard", "--full-name", "-z", "--", ":/"], 0, "new.rs\0");
+
+        assert!(GitCli::new(&script()).check_state(false).is_err());
+        assert!(GitCli::new(&script()).check_state(true).is_ok());
+    }
+}
@@ -0,0 +1,437 @@
+use std::env;
+use std::fs;
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use chrono::{FixedOffset, TimeZone};
+use git2::{self, build::CheckoutBuilder, Commit, DiffOptions, Oid, PushOptions, RemoteCallbacks, Repository, Signature, Sort, Status, StatusOptions};
+use rr_result::RrResult;
+use git::{CommitHash, Git, LogEntry};
+
+/// The changes of the working directory compared to the index.
+const WT_CHANGED: Status = Status::WT_MODIFIED
+    .union(Status::WT_DELETED)
+    .union(Status::WT_TYPECHANGE)
+    .union(Status::WT_RENAMED);
+
+/// The changes of the index compared to HEAD.
+const INDEX_CHANGED: Status = Status::INDEX_NEW
+    .union(Status::INDEX_MODIFIED)
+    .union(Status::INDEX_DELETED)
+    .union(Status::INDEX_RENAMED)
+    .union(Status::INDEX_TYPECHANGE);
+
+/// Implements the git operations by the libgit2 library.
+///
+/// Signing of commits and tags isn't supported and in contrast
+/// to the git command line tool no git hooks are run.
+pub struct Libgit2 {
+    /// a `Repository` can be moved between threads but not shared by them,
+    /// so the `Mutex` makes `Libgit2` a `Git` and serializes its accesses
+    repo: Mutex<Repository>
+}
+
+impl Libgit2 {
+    /// Opens the git repository containing the current directory.
+    pub fn open() -> RrResult<Libgit2> {
+        let repo = Repository::discover(env::current_dir()?)?;
+        Ok(Libgit2 { repo: Mutex::new(repo) })
+    }
+
+    fn repo(&self) -> MutexGuard<'_, Repository> {
+        self.repo.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The paths - relative to the root directory - with a status intersecting `status`.
+    fn paths_with_status(&self, status: Status) -> RrResult<Vec<String>> {
+        let repo = self.repo();
+        let mut options = StatusOptions::new();
+        options.include_untracked(status.intersects(Status::WT_NEW))
+            .recurse_untracked_dirs(true)
+            .include_ignored(false);
+
+        let statuses = repo.statuses(Some(&mut options))?;
+        Ok(statuses.iter()
+           .filter(|e| e.status().intersects(status))
+           .filter_map(|e| e.path().map(str::to_string))
+           .collect())
+    }
+
+    /// The paths with a status intersecting `status` joined to the root directory.
+    fn files_with_status(&self, status: Status) -> RrResult<Vec<PathBuf>> {
+        let root_dir = self.root_dir()?;
+        Ok(self.paths_with_status(status)?.iter().map(|p| root_dir.join(p)).collect())
+    }
+}
+
+impl Git for Libgit2 {
+    fn add(&self, files: &[PathBuf]) -> RrResult<()> {
+        let root_dir = self.root_dir()?;
+        let repo = self.repo();
+        let mut index = repo.index()?;
+        index.read(false)?;
+        for file in files {
+            let path = file.strip_prefix(&root_dir).unwrap_or(file);
+            if root_dir.join(path).exists() {
+                index.add_path(path)?;
+            } else {
+                index.remove_path(path)?;
+            }
+        }
+
+        index.write()?;
+        Ok(())
+    }
+
+    fn modified_files(&self) -> RrResult<Vec<PathBuf>> {
+        self.files_with_status(WT_CHANGED)
+    }
+
+    fn commit(&self, msg: &str, sign: bool) -> RrResult<()> {
+        if sign {
+            return Err("Signing of commits isn't supported by the git backend 'libgit2'!".into());
+        }
+
+        let repo = self.repo();
+        let mut index = repo.index()?;
+        index.read(false)?;
+        let tree = repo.find_tree(index.write_tree()?)?;
+        let parent = repo.head()?.peel_to_commit()?;
+        repo.commit(Some("HEAD"), &signature(&repo, "AUTHOR")?, &signature(&repo, "COMMITTER")?,
+                    &git2::message_prettify(msg, None)?, &tree, &[&parent])?;
+
+        Ok(())
+    }
+
+    fn tag(&self, name: &str, msg: &str, sign: bool) -> RrResult<()> {
+        if sign {
+            return Err("Signing of tags isn't supported by the git backend 'libgit2'!".into());
+        }
+
+        let repo = self.repo();
+        let head = repo.head()?.peel_to_commit()?;
+        repo.tag(name, head.as_object(), &signature(&repo, "COMMITTER")?, &git2::message_prettify(msg, None)?, false)?;
+        Ok(())
+    }
+
+    fn push(&self) -> RrResult<()> {
+        let repo = self.repo();
+        let head = repo.head()?;
+        let branch_ref = head.name().ok_or("Invalid name of current git branch!")?;
+        let remote_name = repo.branch_upstream_remote(branch_ref)?;
+        let remote_name = remote_name.as_str().ok_or("Invalid name of git remote!")?;
+        let upstream_ref = repo.branch_upstream_merge(branch_ref)?;
+        let upstream_ref = upstream_ref.as_str().ok_or("Invalid name of upstream git branch!")?;
+
+        let mut refspecs = vec![format!("{}:{}", branch_ref, upstream_ref)];
+        for tag in repo.tag_names(None)?.iter().flatten() {
+            refspecs.push(format!("refs/tags/{0}:refs/tags/{0}", tag));
+        }
+
+        // the rejection of a reference by the remote isn't an error of 'push' itself
+        let rejected = RefCell::new(Vec::new());
+        {
+            let mut callbacks = RemoteCallbacks::new();
+            callbacks.push_update_reference(|refname, status| {
+                if let Some(status) = status {
+                    rejected.borrow_mut().push(format!("{}: {}", refname, status));
+                }
+
+                Ok(())
+            });
+
+            let mut options = PushOptions::new();
+            options.remote_callbacks(callbacks);
+            repo.find_remote(remote_name)?.push(&refspecs, Some(&mut options))?;
+        }
+
+        let rejected = rejected.into_inner();
+        if ! rejected.is_empty() {
+            return Err(format!("Push to git remote '{}' rejected:\n{}", remote_name, rejected.join("\n")).into());
+        }
+
+        Ok(())
+    }
+
+    fn checkout_into(&self, rev: &str, dir: &Path) -> RrResult<()> {
+        let repo = self.repo();
+        let tree = repo.revparse_single(rev)?.peel_to_tree()?;
+        let mut checkout = CheckoutBuilder::new();
+        checkout.target_dir(dir).update_index(false).force();
+        repo.checkout_tree(tree.as_object(), Some(&mut checkout))?;
+        Ok(())
+    }
+
+    fn remove_checkout(&self, dir: &Path) -> RrResult<()> {
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    fn commits(&self, from: &str, to: Option<&str>, path: Option<&str>) -> RrResult<Vec<LogEntry>> {
+        let pathspec = match path {
+            Some(path) => Some(repo_path(&self.path_in_repo()?, path)),
+            None       => None
+        };
+
+        let repo = self.repo();
+        let mailmap = repo.mailmap()?;
+        let mut commits = Vec::new();
+        for commit in walk(&repo, from, to, pathspec.as_deref())? {
+            let author = commit.author_with_mailmap(&mailmap)?;
+            commits.push(LogEntry {
+                hash: commit.as_object().short_id()?.as_str().unwrap_or_default().to_string(),
+                author: author.name().unwrap_or_default().to_string(),
+                date: date(&author.when()),
+                subject: commit.summary().unwrap_or_default().to_string(),
+                body: commit.body().unwrap_or_default().trim().to_string(),
+                paths: changed_paths(&repo, &commit, pathspec.as_deref())?
+            });
+        }
+
+        Ok(commits)
+    }
+
+    fn authors(&self, rev: &str, path: Option<&str>) -> RrResult<Vec<String>> {
+        let pathspec = match path {
+            Some(path) => Some(repo_path(&self.path_in_repo()?, path)),
+            None       => None
+        };
+
+        let repo = self.repo();
+        let mailmap = repo.mailmap()?;
+        let mut authors = Vec::new();
+        for commit in walk(&repo, rev, None, pathspec.as_deref())? {
+            let author = commit.author_with_mailmap(&mailmap)?.name().unwrap_or_default().to_string();
+            if ! authors.contains(&author) {
+                authors.push(author);
+            }
+        }
+
+        Ok(authors)
+    }
+
+    fn current_branch(&self) -> RrResult<String> {
+        let repo = self.repo();
+        let head = repo.head()?;
+        if ! head.is_branch() {
+            return Ok("HEAD".to_string());
+        }
+
+        Ok(head.shorthand().ok_or("Invalid name of current git branch!")?.to_string())
+    }
+
+    fn upstream(&self) -> RrResult<String> {
+        let repo = self.repo();
+        let head = repo.head()?;
+        let upstream = repo.branch_upstream_name(head.name().ok_or("Invalid name of current git branch!")?)?;
+        let upstream = upstream.as_str().ok_or("Invalid name of upstream git branch!")?;
+        Ok(upstream.trim_start_matches("refs/remotes/").to_string())
+    }
+
+    fn root_dir(&self) -> RrResult<PathBuf> {
+        let repo = self.repo();
+        Ok(repo.workdir().ok_or("Can't operate on a bare git repository!")?.canonicalize()?)
+    }
+
+    fn git_dir(&self) -> RrResult<PathBuf> {
+        Ok(self.repo().path().canonicalize()?)
+    }
+
+    fn path_in_repo(&self) -> RrResult<String> {
+        let root_dir = self.root_dir()?;
+        let curr_dir = env::current_dir()?.canonicalize()?;
+        let path = curr_dir.strip_prefix(&root_dir)
+            .map_err(|_| format!("Current directory '{}' isn't inside of the git repository '{}'!",
+                                 curr_dir.display(), root_dir.display()))?;
+
+        Ok(path.to_string_lossy().into_owned())
+    }
+
+    fn has_tag(&self, name: &str) -> RrResult<bool> {
+        Ok(self.repo().revparse_single(name).is_ok())
+    }
+
+    fn merged_tags(&self) -> RrResult<Vec<String>> {
+        let repo = self.repo();
+        let head = repo.head()?.peel_to_commit()?.id();
+        let mut tags = Vec::new();
+        for name in repo.tag_names(None)?.iter().flatten() {
+            let commit = match repo.revparse_single(name).and_then(|o| o.peel_to_commit()) {
+                Ok(commit) => commit.id(),
+                Err(_)     => continue
+            };
+
+            if commit == head || repo.graph_descendant_of(head, commit)? {
+                tags.push(name.to_string());
+            }
+        }
+
+        Ok(tags)
+    }
+
+    fn has_signing_key(&self) -> RrResult<bool> {
+        let config = self.repo().config()?;
+        Ok(config.get_string("user.signingkey").map(|k| ! k.trim().is_empty()).unwrap_or(false))
+    }
+
+    fn has_dirty_working_dir(&self) -> RrResult<bool> {
+        Ok(! self.paths_with_status(WT_CHANGED)?.is_empty())
+    }
+
+    fn has_staged_changes(&self) -> RrResult<bool> {
+        Ok(! self.paths_with_status(INDEX_CHANGED)?.is_empty())
+    }
+
+    fn untracked_files(&self) -> RrResult<Vec<PathBuf>> {
+        self.files_with_status(Status::WT_NEW)
+    }
+
+    fn remote_update(&self) -> RrResult<()> {
+        let repo = self.repo();
+        for name in repo.remotes()?.iter().flatten() {
+            repo.find_remote(name)?.fetch(&[] as &[&str], None, None)?;
+        }
+
+        Ok(())
+    }
+
+    fn local_head(&self) -> RrResult<CommitHash> {
+        Ok(self.repo().head()?.peel_to_commit()?.id().to_string())
+    }
+
+    fn commit_of(&self, rev: &str) -> RrResult<CommitHash> {
+        Ok(self.repo().revparse_single(rev)?.peel_to_commit()?.id().to_string())
+    }
+
+    fn remote_head(&self) -> RrResult<CommitHash> {
+        let repo = self.repo();
+        let head = repo.head()?;
+        let upstream = repo.branch_upstream_name(head.name().ok_or("Invalid name of current git branch!")?)?;
+        let upstream = upstream.as_str().ok_or("Invalid name of upstream git branch!")?;
+        let remote_head = repo.find_reference(upstream)?.peel_to_commit()?.id();
+        Ok(remote_head.to_string())
+    }
+
+    fn merge_base(&self, commit1: &str, commit2: &str) -> RrResult<CommitHash> {
+        Ok(self.repo().merge_base(Oid::from_str(commit1)?, Oid::from_str(commit2)?)?.to_string())
+    }
+}
+
+/// The signature of the `kind` - 'AUTHOR' or 'COMMITTER' - which is like for the git command line
+/// tool taken from the environment variables `GIT_<kind>_NAME` and `GIT_<kind>_EMAIL` or
+/// otherwise from the git configuration.
+fn signature(repo: &Repository, kind: &str) -> RrResult<Signature<'static>> {
+    let default = repo.signature();
+    let name = env::var(format!("GIT_{}_NAME", kind)).ok();
+    let email = env::var(format!("GIT_{}_EMAIL", kind)).ok();
+    match (name, email, default) {
+        (None, None, default) => Ok(default?),
+        (name, email, default) => {
+            let default = default.ok();
+            let name = name.or_else(|| default.as_ref().and_then(|d| d.name().map(str::to_string)));
+            let email = email.or_else(|| default.as_ref().and_then(|d| d.email().map(str::to_string)));
+            match (name, email) {
+                (Some(name), Some(email)) => Ok(Signature::now(&name, &email)?),
+                _ => Err(format!("Missing git {} name or email! Set git config 'user.name' and 'user.email'!",
+                                 kind.to_lowercase()).into())
+            }
+        }
+    }
+}
+
+/// The `path` relative to the current directory - at `path_in_repo` - as path relative to the root directory.
+fn repo_path(path_in_repo: &str, path: &str) -> String {
+    let path = Path::new(path_in_repo).join(path);
+    let components = path.components()
+        .filter(|c| c.as_os_str() != ".")
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+
+    components.join("/")
+}
+
+/// The commits reachable from `from`, but not from `to` - or all commits reachable from `from`
+/// if `to` is `None` - newest first, optionally only the ones touching `pathspec`.
+fn walk<'r>(repo: &'r Repository, from: &str, to: Option<&str>, pathspec: Option<&str>) -> RrResult<Vec<Commit<'r>>> {
+    let from = repo.revparse_single(from)?.peel_to_commit()?.id();
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(Sort::TIME)?;
+    revwalk.push(from)?;
+
+    // like the range 'to..from' of the git command line tool
+    if let Some(to) = to {
+        revwalk.hide(repo.revparse_single(to)?.peel_to_commit()?.id())?;
+    }
+
+    let mut commits = Vec::new();
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        if let Some(pathspec) = pathspec {
+            if ! touches(repo, &commit, pathspec)? {
+                continue;
+            }
+        }
+
+        commits.push(commit);
+    }
+
+    Ok(commits)
+}
+
+/// The paths changed by `commit` compared to its parent - like they're listed by the git
+/// command line tool with '--name-only' - optionally only the ones at `pathspec`.
+fn changed_paths(repo: &Repository, commit: &Commit, pathspec: Option<&str>) -> RrResult<Vec<String>> {
+    if commit.parent_count() > 1 {
+        return Ok(Vec::new());
+    }
+
+    let mut options = DiffOptions::new();
+    if let Some(pathspec) = pathspec.filter(|p| ! p.is_empty()) {
+        options.pathspec(pathspec);
+    }
+
+    let parent_tree = match commit.parents().next() {
+        Some(parent) => Some(parent.tree()?),
+        None         => None
+    };
+
+    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), Some(&mut options))?;
+    Ok(diff.deltas()
+       .filter_map(|d| d.new_file().path().or_else(|| d.old_file().path()))
+       .map(|p| p.to_string_lossy().into_owned())
+       .collect())
+}
+
+/// The date of `time` in its time zone, e.g. '2019-02-24'.
+fn date(time: &git2::Time) -> String {
+    FixedOffset::east_opt(time.offset_minutes() * 60)
+        .and_then(|offset| offset.timestamp_opt(time.seconds(), 0).single())
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// If `commit` changes files at `pathspec`, which is the case if it differs from all of its parents.
+fn touches(repo: &Repository, commit: &Commit, pathspec: &str) -> RrResult<bool> {
+    if pathspec.is_empty() {
+        return Ok(true);
+    }
+
+    let tree = commit.tree()?;
+    let mut options = DiffOptions::new();
+    options.pathspec(pathspec);
+
+    if commit.parent_count() == 0 {
+        let diff = repo.diff_tree_to_tree(None, Some(&tree), Some(&mut options))?;
+        return Ok(diff.deltas().len() > 0);
+    }
+
+    for parent in commit.parents() {
+        let diff = repo.diff_tree_to_tree(Some(&parent.tree()?), Some(&tree), Some(&mut options))?;
+        if diff.deltas().len() == 0 {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
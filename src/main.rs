@@ -10,15 +10,29 @@ extern crate semver;
 extern crate term;
 extern crate tempfile;
 extern crate dirs;
+extern crate chrono;
+extern crate strsim;
+extern crate shell_words;
+
+#[cfg(feature = "libgit2")]
+extern crate git2;
 
 use std::io::Write;
-use std::path::Path;
-use std::process::Command;
+use std::path::{Path, PathBuf};
 use semver::Version;
 use rr_result::RrResult;
-use config::Config;
+use config::{Config, CommandArgs};
+use template::TemplateVars;
+use pending::Pending;
+use checks::Check;
 use cargo_proj::CargoProj;
-use utils::{check_output, modify_file};
+use git::Git;
+use cargo::Cargo;
+use runner::{RealRunner, RecordingRunner};
+use utils::{choose, confirm, file_list};
+
+#[macro_use]
+mod utils;
 
 mod git;
 mod rr_result;
@@ -26,9 +40,18 @@ mod version_kind;
 mod config;
 mod cargo_proj;
 mod cargo;
+mod changelog;
+mod commit_log;
+mod contributors;
+mod editor;
+mod template;
+mod pending;
+mod checks;
+mod release_tag;
+mod runner;
 
-#[macro_use]
-mod utils;
+#[cfg(feature = "libgit2")]
+mod libgit2;
 
 fn main() {
     execute().unwrap_or_else(|err| {
@@ -38,74 +61,382 @@ fn main() {
 }
 
 fn execute() -> RrResult<()> {
-    let config = Config::from_file_and_command_args()?;
-    let mut cargo_proj = CargoProj::find(&config.start_dir)?;
-    std::env::set_current_dir(cargo_proj.root_dir()?)?;
+    let args = CommandArgs::parse()?;
+    let mut cargo_proj = CargoProj::find(&args.start_dir)?;
+    let config = Config::from_file_and_command_args(args, &cargo_proj)?;
+    if config.check_config {
+        stdoutln!("Configuration is valid!");
+        return Ok(());
+    }
 
-    stdoutln!("Checking git state ...");
-    git::check_state()?;
+    if let Some(ref version) = config.notes {
+        return print_notes(&mut cargo_proj, &config, version);
+    }
 
-    stdoutln!("Testing ...");
-    cargo::test()?;
+    std::env::set_current_dir(cargo_proj.root_dir()?)?;
+
+    let real_runner = RealRunner::new(config.verbose);
+    let runner = RecordingRunner::new(&real_runner);
+    let git = git::open(config.git_backend, &runner)?;
+    let cargo = Cargo::new(&runner, config.offline);
 
     let curr_version = cargo_proj.version().clone();
-    let tag_name_curr_version = config.tag_name(&cargo_proj);
+    let branch = git.current_branch()?;
+    let crate_dir = git.path_in_repo()?;
+
+    if config.print_config {
+        let mut vars = TemplateVars::new(&cargo_proj, &curr_version, &branch, &crate_dir);
+        vars.new_version = config.version_kind.increment(&curr_version);
+        config.print(&vars);
+        return Ok(());
+    }
+
+    if config.finish {
+        return finish(&*git, &cargo);
+    }
+
+    if let Some(pending) = Pending::load(&*git)? {
+        return Err(format!("There's a pending release of {} {}! Complete it first by 'rusty-release --finish'!",
+                           pending.proj_name, pending.version).into());
+    }
+
+    cargo_proj.find_changelog(config.changelog.as_deref())?;
+    if config.changelog_required && cargo_proj.changelog().is_none() {
+        return Err(format!("Couldn't find a changelog in '{}', but 'changelog_required' is set! Add a changelog or set 'changelog'!",
+                           cargo_proj.root_dir()?.display()).into());
+    }
+
+    stdoutln!("Checking git state ...");
+    git.check_state(config.allow_untracked)?;
+
+    if (config.sign_commit || config.sign_tag) && ! git.has_signing_key()? {
+        return Err("Signing of the release requested, but no signing key found! Set git config 'user.signingkey' or create a gpg key for the email of the git committer!".into());
+    }
 
     let new_version = config.version_kind.increment(&curr_version);
-    cargo_proj.write_version(&new_version)?;
+    let new_tag_name = {
+        let mut vars = TemplateVars::new(&cargo_proj, &curr_version, &branch, &crate_dir);
+        vars.new_version = new_version.clone();
+        config.tag_name(&vars)
+    };
+
+    stdoutln!("Checking and testing ...");
+    let mut checks = vec![
+        Check::new("Testing", || cargo.test()),
+        Check::new("Checking release tag", || {
+            if git.has_tag(&new_tag_name)? {
+                return Err(format!("Tag '{}' of the release already exists!", new_tag_name).into());
+            }
+
+            Ok(())
+        })
+    ];
+
+    if config.offline {
+        warnln!("Offline: not checking if the local and the remote git repository are synchronized!");
+    } else {
+        checks.push(Check::new("Checking remote git repository", || git.check_remote()));
+    }
+
+    if config.cargo_publish {
+        checks.push(Check::new("Checking Cargo.toml", || cargo_proj.check_publishable()));
+    }
+
+    checks::run_parallel(checks)?;
+
+    let prev_tag_name = release_tag::find_previous(&git.merged_tags()?, &new_tag_name, &new_version,
+                                                   cargo_proj.name(), ! crate_dir.is_empty());
+
+    let contributors = contributors::format(&contributors::find(&*git, prev_tag_name.as_deref(), &crate_dir)?);
+
+    if config.dry_run || ! config.yes {
+        let mut vars = TemplateVars::new(&cargo_proj, &curr_version, &branch, &crate_dir);
+        vars.new_version = new_version.clone();
+        vars.contributors = contributors.clone();
+        print_release_plan(&*git, &config, &cargo_proj, &vars, &new_tag_name, prev_tag_name.as_deref())?;
+        if config.dry_run {
+            // the release itself isn't simulated, because it writes files and - with
+            // libgit2 - changes the repository without running any command
+            println!("Run git and cargo commands:");
+            for output in runner.records() {
+                println!("{}", output.summary());
+            }
+
+            println!();
+            stdoutln!("Dry run: nothing has been changed!");
+            return Ok(());
+        }
+
+        if ! confirm("Make the release?")? {
+            return Err("Release aborted!".into());
+        }
+    }
+
+    write_version(&cargo, &mut cargo_proj, &new_version)?;
 
     stdoutln!("Building release ...");
-    cargo::build_release()?;
+    cargo.build_release()?;
 
     if let Some(changelog) = cargo_proj.changelog() {
         stdoutln!("Updating changelog ...");
-        update_changelog(config.editor(), changelog, &tag_name_curr_version, &new_version)?;
+        update_changelog(&*git, &config, changelog, prev_tag_name.as_deref(), &new_version, &crate_dir)?;
+        changelog::replace_in_section(changelog, &new_version, "<CONTRIBUTORS>", &contributors)?;
+    }
+
+    let mut vars = TemplateVars::new(&cargo_proj, &curr_version, &branch, &crate_dir);
+    vars.contributors = contributors.clone();
+    if let Some(changelog) = cargo_proj.changelog() {
+        vars.changelog_section = changelog::section(changelog, &new_version)?.unwrap_or_default();
+        write_release_notes(&cargo_proj, &vars.changelog_section)?;
     }
 
-    if git::has_dirty_working_dir()? {
+    if stage_release_files(&*git, &cargo_proj, config.allow_unexpected_changes)? {
         stdoutln!("Creating git commit ...");
-        git::add_update()?;
-        git::commit(&config.commit_message(&cargo_proj))?;
+        git.commit(&config.commit_message(&vars), config.sign_commit)?;
     }
 
     stdoutln!("Creating git tag ...");
-    git::tag(&config.tag_name(&cargo_proj))?;
+    git.tag(&new_tag_name, &config.tag_message(&vars), config.sign_tag)?;
+
+    // the release has to be published before the development version is written
+    if ! config.offline {
+        push_and_publish(&*git, &cargo, config.git_push, config.cargo_publish, config.registry.as_deref())?;
+    }
+
+    if let Some(ref dev_version_kind) = config.dev_version {
+        let dev_version = dev_version_kind.dev_version(&new_version, &config.dev_version_pre)?;
+        stdoutln!("Bumping to development version {} ...", dev_version);
+        write_version(&cargo, &mut cargo_proj, &dev_version)?;
+        if let Some(changelog) = cargo_proj.changelog() {
+            changelog::add_unreleased(changelog)?;
+        }
+
+        let mut dev_vars = TemplateVars::new(&cargo_proj, &new_version, &branch, &crate_dir);
+        dev_vars.changelog_section = vars.changelog_section.clone();
+        dev_vars.contributors = contributors;
+        if stage_release_files(&*git, &cargo_proj, config.allow_unexpected_changes)? {
+            git.commit(&config.dev_commit_message(&dev_vars), config.sign_commit)?;
+        }
+
+        if ! config.offline && config.git_push {
+            stdoutln!("Pushing development version ...");
+            git.push()?;
+        }
+    }
+
+    if config.offline && (config.git_push || config.cargo_publish) {
+        Pending {
+            proj_name: cargo_proj.name().to_string(),
+            version: new_version.to_string(),
+            tag_name: new_tag_name.clone(),
+            root_dir: cargo_proj.root_dir()?.to_path_buf(),
+            git_push: config.git_push,
+            cargo_publish: config.cargo_publish,
+            registry: config.registry.clone()
+        }.save(&*git)?;
 
-    if config.git_push {
+        warnln!("Offline: the git push and the publishing are deferred, complete them by 'rusty-release --finish'!");
+    }
+
+    Ok(())
+}
+
+/// Completes the pending release made in offline mode.
+fn finish(git: &dyn Git, cargo: &Cargo) -> RrResult<()> {
+    let pending = Pending::load(git)?
+        .ok_or("There's no pending release to finish!")?;
+
+    stdoutln!("Finishing release of {} {} ...", pending.proj_name, pending.version);
+    std::env::set_current_dir(&pending.root_dir)?;
+
+    // a development version or later commits might be checked out, so if HEAD
+    // isn't the release, then the release is published from a checkout of its tag
+    if git.commit_of(&pending.tag_name)? == git.local_head()? {
+        push_and_publish(git, cargo, pending.git_push, pending.cargo_publish, pending.registry.as_deref())?;
+    } else {
+        push_and_publish(git, cargo, pending.git_push, false, None)?;
+        if pending.cargo_publish {
+            publish_tag(git, cargo, &pending.tag_name, pending.registry.as_deref())?;
+        }
+    }
+
+    Pending::remove(git)
+}
+
+/// Publishes the cargo project of the current directory from a checkout of `tag_name`.
+fn publish_tag(git: &dyn Git, cargo: &Cargo, tag_name: &str, registry: Option<&str>) -> RrResult<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let checkout_dir = tmp_dir.path().join("checkout");
+    let manifest = checkout_dir.join(git.path_in_repo()?).join("Cargo.toml");
+
+    stdoutln!("Publishing {} to {} ...", tag_name, registry.unwrap_or("crates.io"));
+    git.checkout_into(tag_name, &checkout_dir)?;
+    let result = cargo.publish(registry, Some(&manifest));
+    git.remove_checkout(&checkout_dir)?;
+    result
+}
+
+fn push_and_publish(git: &dyn Git, cargo: &Cargo, git_push: bool, cargo_publish: bool, registry: Option<&str>) -> RrResult<()> {
+    if git_push {
         stdoutln!("Pushing git changes ...");
-        git::push()?;
+        git.push()?;
     }
 
-    if config.cargo_publish {
-        stdoutln!("Publishing to crates.io ...");
-        cargo::publish()?;
+    if cargo_publish {
+        stdoutln!("Publishing to {} ...", registry.unwrap_or("crates.io"));
+        cargo.publish(registry, None)?;
+    }
+
+    Ok(())
+}
+
+/// Prints what the release of `cargo_proj` is going to do.
+fn print_release_plan(git: &dyn Git,
+                      config: &Config,
+                      cargo_proj: &CargoProj,
+                      vars: &TemplateVars,
+                      tag_name: &str,
+                      prev_tag_name: Option<&str>)
+                      -> RrResult<()> {
+    let on_off = |on: bool| if on { "on" } else { "off" };
+    let deferred = |on: bool| if on && config.offline { "deferred till --finish" } else { on_off(on) };
+
+    println!();
+    println!("Project:             {}", cargo_proj.name());
+    println!("Version:             {} -> {}", vars.old_version, vars.new_version);
+    println!("Tag:                 {}{}", tag_name, if config.sign_tag { " (signed)" } else { "" });
+    println!("Previous release:    {}", prev_tag_name.unwrap_or("none, first release"));
+    println!("Commit message:      {}", config.commit_message(vars));
+    println!("Push target:         {}", git.upstream().unwrap_or_else(|_| "no upstream branch".to_string()));
+    println!("Registry:            {}", config.registry.as_deref().unwrap_or("crates.io"));
+    println!();
+    println!("Changelog:           {}", cargo_proj.changelog().map(|c| c.display().to_string()).unwrap_or("off".to_string()));
+    println!("Signed commit:       {}", on_off(config.sign_commit));
+    println!("Development version: {}", config.dev_version.as_ref()
+             .map(|k| k.dev_version(&vars.new_version, &config.dev_version_pre).map(|v| v.to_string()))
+             .unwrap_or(Ok("off".to_string()))?);
+    println!("Git push:            {}", deferred(config.git_push));
+    println!("Publish:             {}", deferred(config.cargo_publish));
+    println!();
+    Ok(())
+}
+
+/// Writes `version` into the `Cargo.toml` and - if present - into the `Cargo.lock`.
+fn write_version(cargo: &Cargo, cargo_proj: &mut CargoProj, version: &Version) -> RrResult<()> {
+    cargo_proj.write_version(version)?;
+    if cargo_proj.cargo_lock().is_some() {
+        cargo.update_lock_file()?;
     }
 
     Ok(())
 }
 
+/// Stages the files changed by the release - the `Cargo.toml`, the `Cargo.lock` and
+/// the changelog - and returns if any file was staged.
+///
+/// Fails if other files have been modified during the release - e.g. by a hook or a
+/// build script - unless `allow_unexpected_changes` is set, then these modifications
+/// are only reported and not staged.
+fn stage_release_files(git: &dyn Git, cargo_proj: &CargoProj, allow_unexpected_changes: bool) -> RrResult<bool> {
+    let mut release_files = vec![cargo_proj.cargo_toml().canonicalize()?];
+    if let Some(cargo_lock) = cargo_proj.cargo_lock() {
+        release_files.push(cargo_lock.canonicalize()?);
+    }
+
+    if let Some(changelog) = cargo_proj.changelog() {
+        release_files.push(changelog.canonicalize()?);
+    }
+
+    let (to_stage, unexpected): (Vec<PathBuf>, Vec<PathBuf>) = git.modified_files()?
+        .into_iter()
+        .partition(|f| f.canonicalize().map(|f| release_files.contains(&f)).unwrap_or(false));
+
+    if ! unexpected.is_empty() {
+        let files = file_list(&unexpected);
+        if ! allow_unexpected_changes {
+            return Err(format!("Unexpected modifications of files during the release:\n{}\nClear them or set 'allow_unexpected_changes'!", files).into());
+        }
+
+        warnln!("Ignoring unexpected modifications of files during the release:\n{}", files);
+    }
+
+    if to_stage.is_empty() {
+        return Ok(false);
+    }
+
+    git.add(&to_stage)?;
+    Ok(true)
+}
+
+/// Prints the section of `version` from the changelog.
+fn print_notes(cargo_proj: &mut CargoProj, config: &Config, version: &Version) -> RrResult<()> {
+    cargo_proj.find_changelog(config.changelog.as_deref())?;
+    let changelog = cargo_proj.changelog()
+        .ok_or(format!("Couldn't find a changelog in '{}'!", cargo_proj.root_dir()?.display()))?;
+
+    let notes = changelog::section(changelog, version)?
+        .ok_or(format!("Couldn't find the release notes of {} in '{}'!", version, changelog.display()))?;
+
+    println!("{}", notes);
+    Ok(())
+}
+
+/// Writes the release `notes` into the file 'release-notes.md' in the target directory,
+/// e.g. for the description of a release on a code hosting platform.
+fn write_release_notes(cargo_proj: &CargoProj, notes: &str) -> RrResult<()> {
+    let target_dir = cargo_proj.target_dir()?;
+    std::fs::create_dir_all(&target_dir)?;
+
+    let release_notes = target_dir.join("release-notes.md");
+    stdoutln!("Writing release notes to '{}' ...", release_notes.display());
+    std::fs::write(&release_notes, format!("{}\n", notes))?;
+    Ok(())
+}
+
 /// Adds `new_version` at the top of the `changelog` and opens
-/// `changelog` and a temporary file containing the commits from
-/// HEAD till the last release.
-fn update_changelog(mut editor_cmd: Command,
+/// `changelog` and a temporary file containing the commits from HEAD till the previous
+/// release `prev_tag_name` - or all commits for the first release - only the ones
+/// touching the project if it resides in the sub directory `crate_dir` of the repository.
+///
+/// If the section of `new_version` is still empty after closing the editor, it
+/// asks to reopen the editor, to abort or to continue the release anyway - with
+/// `--yes` it continues with a warning.
+fn update_changelog(git: &dyn Git,
+                    config: &Config,
                     changelog: &Path,
-                    tag_name_curr_version: &str,
-                    new_version: &Version)
+                    prev_tag_name: Option<&str>,
+                    new_version: &Version,
+                    crate_dir: &str)
                     -> RrResult<()> {
-    modify_file(changelog, |contents| { format!("{}\n\n{}", new_version, contents) })?;
+    changelog::add_version(changelog, new_version)?;
 
-    let log_to = if git::has_tag(tag_name_curr_version)? {
-        Some(tag_name_curr_version)
+    let log_file = if config.commit_log {
+        Some(commit_log::write(git, prev_tag_name, crate_dir, &config.log_format, config.log_group)?)
     } else {
         None
     };
 
-    let log_file = git::log_file("HEAD", log_to)?;
+    loop {
+        // the editor inherits the terminal, so that terminal editors can be used
+        let status = config.editor(changelog, log_file.as_ref().map(|f| f.path()))?.status()?;
+        if ! status.success() {
+            return Err(format!("The editor failed ({})!", status).into());
+        }
 
-    let output = editor_cmd.arg(changelog)
-        .arg(log_file.path())
-        .output()?;
+        if changelog::section(changelog, new_version)?.is_some() {
+            return Ok(());
+        }
 
-    check_output(&output)?;
-    Ok(())
+        warnln!("The changelog section of {} is empty!", new_version);
+        if config.yes {
+            return Ok(());
+        }
+
+        match choose("Reopen the editor, abort or continue the release?", &["reopen", "abort", "continue"])? {
+            Some(0) => continue,
+            Some(2) => return Ok(()),
+            _       => return Err(format!("Release aborted! The version and the changelog '{}' have already been modified.",
+                                          changelog.display()).into())
+        }
+    }
 }
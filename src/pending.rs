@@ -0,0 +1,71 @@
+use std::fs;
+use std::path::PathBuf;
+use toml;
+use rr_result::RrResult;
+use utils::map_file;
+use git::Git;
+
+/// The steps of a release deferred by the offline mode, which
+/// are completed by a later call of `rusty-release --finish`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Pending {
+    /// the name of the released cargo project
+    pub proj_name: String,
+
+    /// the released version
+    pub version: String,
+
+    /// the git tag of the released version
+    pub tag_name: String,
+
+    /// the root directory of the released cargo project
+    pub root_dir: PathBuf,
+
+    /// push to git remote repository
+    pub git_push: bool,
+
+    /// publish to crates.io
+    pub cargo_publish: bool,
+
+    /// the registry to publish to instead of crates.io
+    pub registry: Option<String>
+}
+
+impl Pending {
+    /// Loads the pending release of the current git repository.
+    pub fn load(git: &dyn Git) -> RrResult<Option<Pending>> {
+        let path = pending_file(git)?;
+        if ! path.is_file() {
+            return Ok(None);
+        }
+
+        map_file(&path, |contents| {
+            let pending = toml::from_str(&contents)
+                .map_err(|e| format!("Couldn't read pending release from '{}': {}", path.display(), e))?;
+
+            Ok(Some(pending))
+        })
+    }
+
+    /// Saves the pending release into the current git repository.
+    pub fn save(&self, git: &dyn Git) -> RrResult<()> {
+        fs::write(pending_file(git)?, toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Removes the pending release from the current git repository.
+    pub fn remove(git: &dyn Git) -> RrResult<()> {
+        let path = pending_file(git)?;
+        if path.is_file() {
+            fs::remove_file(path)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The pending release is stored inside of the git directory,
+/// so that it doesn't show up as an untracked file.
+fn pending_file(git: &dyn Git) -> RrResult<PathBuf> {
+    Ok(git.git_dir()?.join("rusty-release-pending.toml"))
+}
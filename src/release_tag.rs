@@ -0,0 +1,79 @@
+use semver::Version;
+
+/// Finds the tag of the previous release in `tags` - the ones merged into HEAD -
+/// which is the tag with the highest version below the released `version`.
+///
+/// The tags are matched against the tag name `tag_name` of the release - with its version
+/// replaced by any other one - and against the common patterns 'v<VERSION>', '<VERSION>',
+/// '<PROJ_NAME>-v<VERSION>' and '<PROJ_NAME>-<VERSION>', so that the previous release is
+/// found even if the tag template has been changed since then.
+///
+/// If the cargo project resides in a sub directory of the git repository (`in_sub_dir`), then
+/// the common patterns without the project name aren't considered, because tags like 'v1.2.0'
+/// might belong to another cargo project of the repository.
+pub fn find_previous(tags: &[String], tag_name: &str, version: &Version, proj_name: &str, in_sub_dir: bool) -> Option<String> {
+    let mut patterns = Vec::new();
+    if let Some(start) = tag_name.find(&version.to_string()) {
+        let end = start + version.to_string().len();
+        patterns.push((tag_name[..start].to_string(), tag_name[end..].to_string()));
+    }
+
+    patterns.push((format!("{}-v", proj_name), String::new()));
+    patterns.push((format!("{}-", proj_name), String::new()));
+    if ! in_sub_dir {
+        patterns.push(("v".to_string(), String::new()));
+        patterns.push((String::new(), String::new()));
+    }
+
+    tags.iter()
+        .filter_map(|tag| tag_version(tag, &patterns).map(|(v, pattern)| (v, pattern, tag)))
+        .filter(|(v, _, _)| v < version)
+        // for tags with the same version prefer the earlier pattern
+        .max_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)))
+        .map(|(_, _, tag)| tag.clone())
+}
+
+/// The version of `tag` and the index of the first of the `patterns` - prefixes
+/// and suffixes surrounding the version - matching it.
+fn tag_version(tag: &str, patterns: &[(String, String)]) -> Option<(Version, usize)> {
+    patterns.iter()
+        .enumerate()
+        .filter_map(|(i, (prefix, suffix))| {
+            let version = tag.strip_prefix(prefix.as_str())?.strip_suffix(suffix.as_str())?;
+            Version::parse(version).ok().map(|v| (v, i))
+        })
+        .next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn previous(tags: &[&str], tag_name: &str, version: &str, in_sub_dir: bool) -> Option<String> {
+        let tags = tags.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        find_previous(&tags, tag_name, &Version::parse(version).unwrap(), "foo", in_sub_dir)
+    }
+
+    #[test]
+    fn finds_highest_version_below_release() {
+        let tags = ["v0.1.0", "v0.2.0-rc.1", "v0.2.0", "v0.10.0", "v1.0.0", "docs"];
+        assert_eq!(previous(&tags, "v0.11.0", "0.11.0", false).as_deref(), Some("v0.10.0"));
+        assert_eq!(previous(&tags, "v0.2.0", "0.2.0", false).as_deref(), Some("v0.2.0-rc.1"));
+        assert_eq!(previous(&tags, "v0.1.0", "0.1.0", false), None);
+    }
+
+    #[test]
+    fn matches_changed_tag_templates() {
+        let tags = ["0.1.0", "v0.2.0", "foo-v0.3.0", "release/0.4.0"];
+        assert_eq!(previous(&tags, "release/0.5.0", "0.5.0", false).as_deref(), Some("release/0.4.0"));
+        assert_eq!(previous(&tags, "foo-0.4.0", "0.4.0", false).as_deref(), Some("foo-v0.3.0"));
+        assert_eq!(previous(&tags, "rel-0.3.0", "0.3.0", false).as_deref(), Some("v0.2.0"));
+    }
+
+    #[test]
+    fn ignores_tags_without_project_name_in_sub_directory() {
+        let tags = ["v0.3.0", "bar-v0.2.0", "foo-v0.1.0"];
+        assert_eq!(previous(&tags, "foo-v0.4.0", "0.4.0", true).as_deref(), Some("foo-v0.1.0"));
+        assert_eq!(previous(&tags, "foo-v0.4.0", "0.4.0", false).as_deref(), Some("v0.3.0"));
+    }
+}
@@ -1,10 +1,14 @@
 use std::io;
+use std::path::PathBuf;
 use std::convert::From;
 use std::fmt::{self, Display, Formatter};
 use semver::SemVerError;
 use term;
 use toml;
 
+#[cfg(feature = "libgit2")]
+use git2;
+
 /// The result type used in `rusty-release`.
 pub type RrResult<T> = Result<T, RrError>;
 
@@ -12,13 +16,23 @@ pub type RrResult<T> = Result<T, RrError>;
 #[derive(Clone, Debug)]
 pub enum RrError {
     /// generic error message
-    Message(String)
+    Message(String),
+
+    /// invalid configuration file at `path`, the error
+    /// `msg` is optionally located at `line`
+    Config { path: PathBuf, line: Option<usize>, msg: String }
 }
 
 impl Display for RrError {
     fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
         match *self {
             RrError::Message(ref msg) => writeln!(f, "{}", msg),
+
+            RrError::Config { ref path, line: Some(line), ref msg } =>
+                writeln!(f, "Invalid configuration file '{}', line {}: {}", path.display(), line, msg),
+
+            RrError::Config { ref path, line: None, ref msg } =>
+                writeln!(f, "Invalid configuration file '{}': {}", path.display(), msg)
         }
     }
 }
@@ -49,13 +63,26 @@ impl From<toml::de::Error> for RrError {
     }
 }
 
+impl From<toml::ser::Error> for RrError {
+    fn from(err: toml::ser::Error) -> RrError {
+        RrError::Message(err.to_string())
+    }
+}
+
+#[cfg(feature = "libgit2")]
+impl From<git2::Error> for RrError {
+    fn from(err: git2::Error) -> RrError {
+        RrError::Message(err.message().to_string())
+    }
+}
+
 impl From<String> for RrError {
     fn from(s: String) -> RrError {
         RrError::Message(s)
     }
 }
 
-impl<'a> From<&'a str> for RrError {
+impl From<&str> for RrError {
     fn from(s: &str) -> RrError {
         RrError::Message(s.to_owned())
     }
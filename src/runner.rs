@@ -0,0 +1,271 @@
+use std::ffi::{OsStr, OsString};
+use std::fmt::{self, Display, Formatter};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use rr_result::RrResult;
+
+#[cfg(test)]
+use std::collections::VecDeque;
+
+/// A command to run, the program and its arguments.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cmd {
+    program: OsString,
+    args: Vec<OsString>
+}
+
+impl Cmd {
+    pub fn new<S: AsRef<OsStr>>(program: S) -> Cmd {
+        Cmd { program: program.as_ref().to_os_string(), args: Vec::new() }
+    }
+
+    pub fn arg<S: AsRef<OsStr>>(mut self, arg: S) -> Cmd {
+        self.args.push(arg.as_ref().to_os_string());
+        self
+    }
+
+    pub fn args<I, S>(mut self, args: I) -> Cmd
+        where I: IntoIterator<Item = S>,
+              S: AsRef<OsStr>
+    {
+        self.args.extend(args.into_iter().map(|a| a.as_ref().to_os_string()));
+        self
+    }
+
+    /// The program followed by its arguments.
+    #[cfg(test)]
+    pub fn argv(&self) -> Vec<String> {
+        Some(&self.program).into_iter()
+            .chain(self.args.iter())
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect()
+    }
+}
+
+impl Display for Cmd {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{}", self.program.to_string_lossy())?;
+        for arg in &self.args {
+            let arg = arg.to_string_lossy();
+            if arg.is_empty() || arg.contains(char::is_whitespace) {
+                write!(f, " '{}'", arg)?;
+            } else {
+                write!(f, " {}", arg)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The output of a finished `Cmd`.
+#[derive(Clone, Debug)]
+pub struct CmdOutput {
+    /// the command which produced the output
+    pub cmd: Cmd,
+
+    /// the exit code, `None` if the command has been terminated by a signal
+    pub code: Option<i32>,
+
+    pub stdout: String,
+    pub stderr: String,
+
+    /// how long the command was running
+    pub duration: Duration
+}
+
+impl CmdOutput {
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+
+    /// Fails with the command, its exit code, its running time and its
+    /// error output if the command didn't succeed.
+    pub fn check(self) -> RrResult<CmdOutput> {
+        if self.success() {
+            return Ok(self);
+        }
+
+        let msg = if self.stderr.trim().is_empty() { &self.stdout } else { &self.stderr };
+        Err(format!("'{}' failed ({}, after {:.2}s):\n{}",
+                    self.cmd, self.status(), self.duration.as_secs_f64(), msg.trim_end()).into())
+    }
+
+    /// The exit code - or the termination by a signal - for messages.
+    pub fn status(&self) -> String {
+        self.code.map(|c| format!("exit code {}", c)).unwrap_or("terminated by signal".to_string())
+    }
+
+    /// The command with its exit code and running time for the logs.
+    pub fn summary(&self) -> String {
+        format!("$ {} ({}, {:.2}s)", self.cmd, self.status(), self.duration.as_secs_f64())
+    }
+
+    /// The standard output without the surrounding whitespace.
+    pub fn stdout_trimmed(&self) -> &str {
+        self.stdout.trim()
+    }
+}
+
+/// Runs the commands of git and cargo.
+///
+/// Is `Sync`, so that the `GitCli` and the `Cargo` borrowing
+/// a runner can be shared between threads.
+pub trait CommandRunner: Sync {
+    fn run(&self, cmd: &Cmd) -> RrResult<CmdOutput>;
+}
+
+/// Runs the commands as child processes.
+pub struct RealRunner {
+    /// log every finished command with its exit code and running time to stderr
+    verbose: bool
+}
+
+impl RealRunner {
+    pub fn new(verbose: bool) -> RealRunner {
+        RealRunner { verbose }
+    }
+}
+
+impl CommandRunner for RealRunner {
+    fn run(&self, cmd: &Cmd) -> RrResult<CmdOutput> {
+        let start = Instant::now();
+        let output = Command::new(&cmd.program)
+            .args(&cmd.args)
+            .output()
+            .map_err(|e| format!("Couldn't run '{}': {}", cmd, e))?;
+
+        let output = CmdOutput {
+            cmd: cmd.clone(),
+            code: output.status.code(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            duration: start.elapsed()
+        };
+
+        // one line per command, so that the logs of the parallel checks don't interleave
+        if self.verbose {
+            eprintln!("{}", output.summary());
+        }
+
+        Ok(output)
+    }
+}
+
+/// Records the commands and forwards them to an inner runner, or - in tests -
+/// doesn't run them at all and answers them by a successful, empty output.
+pub struct RecordingRunner<'a> {
+    inner: Option<&'a dyn CommandRunner>,
+    records: Mutex<Vec<CmdOutput>>
+}
+
+impl<'a> RecordingRunner<'a> {
+    pub fn new(inner: &'a dyn CommandRunner) -> RecordingRunner<'a> {
+        RecordingRunner { inner: Some(inner), records: Mutex::new(Vec::new()) }
+    }
+
+    #[cfg(test)]
+    pub fn fake() -> RecordingRunner<'static> {
+        RecordingRunner { inner: None, records: Mutex::new(Vec::new()) }
+    }
+
+    /// The outputs of the run commands in the order of their execution.
+    pub fn records(&self) -> Vec<CmdOutput> {
+        self.records.lock().unwrap().clone()
+    }
+
+    /// The argument vectors of the run commands in the order of their execution.
+    #[cfg(test)]
+    pub fn argvs(&self) -> Vec<Vec<String>> {
+        self.records().iter().map(|r| r.cmd.argv()).collect()
+    }
+}
+
+impl<'a> CommandRunner for RecordingRunner<'a> {
+    fn run(&self, cmd: &Cmd) -> RrResult<CmdOutput> {
+        let output = match self.inner {
+            Some(inner) => inner.run(cmd)?,
+            None        => fake_output(cmd, 0, "")
+        };
+
+        self.records.lock().unwrap().push(output.clone());
+        Ok(output)
+    }
+}
+
+/// Answers the expected commands - in the expected order - by scripted outputs,
+/// panics on any other command.
+#[cfg(test)]
+pub struct ScriptedRunner {
+    script: Mutex<VecDeque<(Vec<String>, i32, String)>>
+}
+
+#[cfg(test)]
+impl ScriptedRunner {
+    pub fn new() -> ScriptedRunner {
+        ScriptedRunner { script: Mutex::new(VecDeque::new()) }
+    }
+
+    /// Expects the command `argv` next, which exits with `code` and prints `stdout`.
+    pub fn expect(self, argv: &[&str], code: i32, stdout: &str) -> ScriptedRunner {
+        self.script.lock().unwrap().push_back((argv.iter().map(|a| a.to_string()).collect(), code, stdout.to_string()));
+        self
+    }
+
+    /// Panics if not all expected commands have been run.
+    pub fn assert_done(&self) {
+        let script = self.script.lock().unwrap();
+        assert!(script.is_empty(), "Expected commands haven't been run: {:?}", script);
+    }
+}
+
+#[cfg(test)]
+impl CommandRunner for ScriptedRunner {
+    fn run(&self, cmd: &Cmd) -> RrResult<CmdOutput> {
+        let (argv, code, stdout) = self.script.lock().unwrap().pop_front()
+            .unwrap_or_else(|| panic!("Unexpected command: {}", cmd));
+
+        assert_eq!(cmd.argv(), argv, "Unexpected command: {}", cmd);
+        Ok(fake_output(cmd, code, &stdout))
+    }
+}
+
+fn fake_output(cmd: &Cmd, code: i32, stdout: &str) -> CmdOutput {
+    CmdOutput {
+        cmd: cmd.clone(),
+        code: Some(code),
+        stdout: stdout.to_string(),
+        stderr: String::new(),
+        duration: Duration::from_secs(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn displays_command_with_quoted_arguments() {
+        let cmd = Cmd::new("git").arg("commit").arg("--message=foo 0.1.1").arg("");
+        assert_eq!(cmd.to_string(), "git commit '--message=foo 0.1.1' ''");
+    }
+
+    #[test]
+    fn failed_command_reports_command_and_exit_code() {
+        let runner = ScriptedRunner::new().expect(&["git", "push"], 1, "rejected");
+        let err = runner.run(&Cmd::new("git").arg("push")).unwrap().check().unwrap_err();
+        assert!(err.to_string().starts_with("'git push' failed (exit code 1, after 0.00s):\nrejected"));
+    }
+
+    #[test]
+    fn real_runner_captures_output() {
+        let runner = RealRunner::new(false);
+        let recorder = RecordingRunner::new(&runner);
+        let output = recorder.run(&Cmd::new("sh").arg("-c").arg("echo out; echo err >&2; exit 3")).unwrap();
+        assert_eq!(output.code, Some(3));
+        assert_eq!(output.stdout, "out\n");
+        assert_eq!(output.stderr, "err\n");
+        assert_eq!(recorder.argvs(), [["sh", "-c", "echo out; echo err >&2; exit 3"]]);
+    }
+}
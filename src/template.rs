@@ -0,0 +1,189 @@
+use std::env;
+use chrono::Local;
+use semver::Version;
+use rr_result::RrResult;
+use cargo_proj::CargoProj;
+
+/// The placeholders supported by a `Template`, additionally
+/// environment variables can be referenced by '<ENV:NAME>'.
+const PLACEHOLDERS: &[&str] = &[
+    "PROJ_NAME",
+    "OLD_VERSION",
+    "NEW_VERSION",
+    "MAJOR",
+    "MINOR",
+    "PATCH",
+    "PRE",
+    "DATE",
+    "BRANCH",
+    "CRATE_DIR",
+    "CHANGELOG_SECTION",
+    "CONTRIBUTORS"
+];
+
+/// The prefix of a placeholder referencing an environment variable.
+const ENV_PREFIX: &str = "ENV:";
+
+/// The values for the placeholders of a `Template`.
+pub trait Vars {
+    /// The value of `placeholder` - its name without the angle brackets -
+    /// or `None` if it isn't supported.
+    fn value(&self, placeholder: &str) -> Option<String>;
+}
+
+/// Represents a string template that contains placeholders - like '<PROJ_NAME>' -
+/// that can be replaced by the values of `Vars`, e.g. of `TemplateVars`.
+#[derive(Debug)]
+pub struct Template<'a>(pub &'a str);
+
+impl<'a> Template<'a> {
+    pub fn render<V: Vars>(&self, vars: &V) -> String {
+        let mut rendered = String::new();
+        let mut rest = self.0;
+        while let Some((start, name, end)) = next_placeholder(rest) {
+            rendered.push_str(&rest[..start]);
+            let value = match name.strip_prefix(ENV_PREFIX) {
+                Some(var) => Some(env::var(var).unwrap_or_default()),
+                None      => vars.value(name)
+            };
+
+            match value {
+                Some(value) => rendered.push_str(&value),
+                None        => rendered.push_str(&rest[start..end])
+            }
+
+            rest = &rest[end..];
+        }
+
+        rendered.push_str(rest);
+        rendered
+    }
+
+    /// Checks that the template only contains the placeholders of `TemplateVars`
+    /// and that referenced environment variables are set.
+    pub fn check(&self) -> RrResult<()> {
+        self.check_placeholders(PLACEHOLDERS)
+    }
+
+    /// Checks that the template only contains the `placeholders`
+    /// and that referenced environment variables are set.
+    pub fn check_placeholders(&self, placeholders: &[&str]) -> RrResult<()> {
+        let mut rest = self.0;
+        while let Some((_, name, end)) = next_placeholder(rest) {
+            if let Some(var) = name.strip_prefix(ENV_PREFIX) {
+                if env::var(var).is_err() {
+                    return Err(format!("Environment variable '{}' of placeholder '<{}>' in template '{}' isn't set!",
+                                       var, name, self.0).into());
+                }
+            } else if ! placeholders.contains(&name) {
+                return Err(format!("Unknown placeholder '<{}>' in template '{}'! Supported placeholders: {}, <ENV:NAME>",
+                                   name, self.0,
+                                   placeholders.iter().map(|p| format!("<{}>", p)).collect::<Vec<_>>().join(", ")).into());
+            }
+
+            rest = &rest[end..];
+        }
+
+        Ok(())
+    }
+}
+
+/// The values for the placeholders of a `Template`.
+#[derive(Debug)]
+pub struct TemplateVars {
+    /// the name of the cargo project
+    pub proj_name: String,
+
+    /// the version before the release
+    pub old_version: Version,
+
+    /// the version of the release
+    pub new_version: Version,
+
+    /// the current date
+    pub date: String,
+
+    /// the current git branch
+    pub branch: String,
+
+    /// the directory of the cargo project relative to the root directory
+    /// of the git repository, empty if both are the same
+    pub crate_dir: String,
+
+    /// the section of the new version in the changelog
+    pub changelog_section: String,
+
+    /// the authors of the commits since the previous release
+    pub contributors: String
+}
+
+impl TemplateVars {
+    /// The values for a release of `proj` with its current version, coming from
+    /// `old_version` on the git branch `branch`, where `proj` resides in the
+    /// directory `crate_dir` of the git repository.
+    pub fn new(proj: &CargoProj, old_version: &Version, branch: &str, crate_dir: &str) -> TemplateVars {
+        TemplateVars {
+            proj_name: proj.name().to_string(),
+            old_version: old_version.clone(),
+            new_version: proj.version().clone(),
+            date: Local::now().format("%Y-%m-%d").to_string(),
+            branch: branch.to_string(),
+            crate_dir: crate_dir.to_string(),
+            changelog_section: String::new(),
+            contributors: String::new()
+        }
+    }
+}
+
+impl Vars for TemplateVars {
+    fn value(&self, placeholder: &str) -> Option<String> {
+        let value = match placeholder {
+            "PROJ_NAME"         => self.proj_name.clone(),
+            "OLD_VERSION"       => self.old_version.to_string(),
+            "NEW_VERSION"       => self.new_version.to_string(),
+            "MAJOR"             => self.new_version.major.to_string(),
+            "MINOR"             => self.new_version.minor.to_string(),
+            "PATCH"             => self.new_version.patch.to_string(),
+            "PRE"               => self.new_version.pre.iter().map(|i| i.to_string()).collect::<Vec<_>>().join("."),
+            "DATE"              => self.date.clone(),
+            "BRANCH"            => self.branch.clone(),
+            "CRATE_DIR"         => self.crate_dir.clone(),
+            "CHANGELOG_SECTION" => self.changelog_section.clone(),
+            "CONTRIBUTORS"      => self.contributors.clone(),
+            _                   => return None
+        };
+
+        Some(value)
+    }
+}
+
+/// Finds the next placeholder in `text` and returns its start index,
+/// its name - without the angle brackets - and its end index.
+///
+/// Only upper case names - optionally with a prefix like 'ENV:' - are
+/// considered as placeholders, so that other text in angle brackets -
+/// like an email address - stays untouched.
+fn next_placeholder(text: &str) -> Option<(usize, &str, usize)> {
+    let mut offset = 0;
+    while let Some(start) = text[offset..].find('<').map(|i| i + offset) {
+        if let Some(len) = text[start + 1..].find('>') {
+            let name = &text[start + 1..start + 1 + len];
+            if is_placeholder_name(name) {
+                return Some((start, name, start + len + 2));
+            }
+        }
+
+        offset = start + 1;
+    }
+
+    None
+}
+
+fn is_placeholder_name(name: &str) -> bool {
+    if let Some(var) = name.strip_prefix(ENV_PREFIX) {
+        ! var.is_empty() && var.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    } else {
+        name.starts_with(|c: char| c.is_ascii_uppercase())
+            && name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+    }
+}
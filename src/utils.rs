@@ -1,20 +1,55 @@
-use std::process::Output;
 use std::fs::{File, OpenOptions};
-use std::io::{Read, Write, Seek, SeekFrom};
-use std::path::Path;
+use std::io::{self, Read, Write, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
 use rr_result::RrResult;
 
-pub fn check_output(out: &Output) -> RrResult<()> {
-    if out.status.success() {
-        return Ok(());
-    }
+/// Asks the yes/no `question` on the terminal and returns
+/// if it has been answered by 'y' or 'yes'.
+pub fn confirm(question: &str) -> RrResult<bool> {
+    print!("{} [y/N] ", question);
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+
+    let answer = answer.trim().to_lowercase();
+    Ok(answer == "y" || answer == "yes")
+}
 
-    let mut msg = String::from_utf8_lossy(&out.stderr).into_owned();
-    if msg.is_empty() {
-        msg = String::from_utf8_lossy(&out.stdout).into_owned();
+/// Asks the `question` on the terminal offering the `choices` - each one selectable by its
+/// first letter - and returns the index of the selected choice, or `None` if the input ended.
+pub fn choose(question: &str, choices: &[&str]) -> RrResult<Option<usize>> {
+    let options = choices.iter()
+        .map(|c| format!("[{}]{}", &c[..1], &c[1..]))
+        .collect::<Vec<_>>()
+        .join("/");
+
+    loop {
+        print!("{} {} ", question, options);
+        io::stdout().flush()?;
+
+        let mut answer = String::new();
+        if io::stdin().read_line(&mut answer)? == 0 {
+            return Ok(None);
+        }
+
+        let answer = answer.trim().to_lowercase();
+        if answer.is_empty() {
+            continue;
+        }
+
+        if let Some(index) = choices.iter().position(|c| c.starts_with(&answer)) {
+            return Ok(Some(index));
+        }
     }
+}
 
-    Err(msg.into())
+/// Formats `files` as an indented list with one file per line.
+pub fn file_list(files: &[PathBuf]) -> String {
+    files.iter()
+        .map(|f| format!("  {}", f.display()))
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 /// Reads `file` into a string which is passed to the function `f`
@@ -32,7 +67,7 @@ pub fn modify_file<F>(file: &Path, f: F) -> RrResult<()>
 
     let contents = f(contents);
 
-    file.set_len(contents.as_bytes().len() as u64)?;
+    file.set_len(contents.len() as u64)?;
     file.seek(SeekFrom::Start(0))?;
     file.write_all(contents.as_bytes())?;
     Ok(())
@@ -76,6 +111,30 @@ macro_rules! stdoutln {
     }};
 }
 
+macro_rules! warnln {
+    ($fmt:expr) => {{
+        use term;
+
+        let mut stderr = term::stderr().unwrap();
+        stderr.fg(term::color::YELLOW).unwrap();
+
+        writeln!(stderr, $fmt).unwrap();
+
+        stderr.reset().unwrap();
+    }};
+
+    ($fmt:expr, $($arg:tt)*) => {{
+        use term;
+
+        let mut stderr = term::stderr().unwrap();
+        stderr.fg(term::color::YELLOW).unwrap();
+
+        writeln!(stderr, $fmt, $($arg)*).unwrap();
+
+        stderr.reset().unwrap();
+    }};
+}
+
 macro_rules! stderrln {
     ($fmt:expr) => {{
         use term;
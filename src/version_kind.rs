@@ -1,4 +1,5 @@
 use semver::Version;
+use rr_result::RrResult;
 
 // A part of a semver version: Major.Minor.Patch
 arg_enum! {
@@ -19,9 +20,18 @@ arg_enum! {
 }
 
 impl VersionKind {
+    /// Increments the part of `version` given by the version kind.
+    ///
+    /// A pre-release version - like '1.5.0-dev' - is released without its
+    /// pre-release part if the version part already is incremented, so '1.5.0-dev'
+    /// becomes '1.5.0' for `Minor` and `Patch`, but '2.0.0' for `Major`.
     pub fn increment(&self, version: &Version) -> Version {
         let mut new_vers = version.clone();
+        let is_pre = version.is_prerelease();
         match *self {
+            VersionKind::Major if is_pre && version.minor == 0 && version.patch == 0 => strip_pre(&mut new_vers),
+            VersionKind::Minor if is_pre && version.patch == 0                       => strip_pre(&mut new_vers),
+            VersionKind::Patch if is_pre                                             => strip_pre(&mut new_vers),
             VersionKind::Major   => new_vers.increment_major(),
             VersionKind::Minor   => new_vers.increment_minor(),
             VersionKind::Patch   => new_vers.increment_patch(),
@@ -30,4 +40,27 @@ impl VersionKind {
 
         new_vers
     }
+
+    /// The development version following the release `version`, which is
+    /// `version` incremented by the version kind with the pre-release part `pre`,
+    /// e.g. '1.5.0-dev' for the release '1.4.0', `Minor` and the `pre` 'dev'.
+    pub fn dev_version(&self, version: &Version, pre: &str) -> RrResult<Version> {
+        let mut dev_vers = version.clone();
+        match *self {
+            VersionKind::Major   => dev_vers.increment_major(),
+            VersionKind::Minor   => dev_vers.increment_minor(),
+            VersionKind::Patch   => dev_vers.increment_patch(),
+            VersionKind::Current => return Err("Invalid version kind 'Current' for the development version!".into())
+        }
+
+        let dev_vers = Version::parse(&format!("{}-{}", dev_vers, pre))
+            .map_err(|e| format!("Invalid pre-release part '{}' for the development version: {}", pre, e))?;
+
+        Ok(dev_vers)
+    }
+}
+
+fn strip_pre(version: &mut Version) {
+    version.pre.clear();
+    version.build.clear();
 }
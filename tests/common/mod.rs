@@ -0,0 +1,250 @@
+//! Helpers for running releases against throwaway git repositories.
+
+use std::env;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+/// A cargo project in a temporary git repository with a bare 'remote'
+/// repository and a stub `cargo`, which records its invocations and
+/// only runs the real cargo for `cargo metadata`.
+pub struct TestRepo {
+    /// the temporary directory containing everything
+    dir: TempDir,
+
+    /// the directory of the cargo project inside of the git repository
+    crate_dir: PathBuf,
+
+    /// the git backend used by `rusty-release`
+    git_backend: &'static str
+}
+
+impl TestRepo {
+    /// A cargo project 'foo' with version '0.1.0', a changelog and a release tag 'v0.1.0'
+    /// in the root directory of the git repository, released with `git_backend`.
+    pub fn new(git_backend: &'static str) -> TestRepo {
+        TestRepo::with_crate_dir(git_backend, "")
+    }
+
+    /// Like `new`, but the cargo project resides in the directory
+    /// `crate_dir` of the git repository.
+    pub fn with_crate_dir(git_backend: &'static str, crate_dir: &str) -> TestRepo {
+        let dir = tempfile::Builder::new().prefix("rusty-release-test").tempdir().unwrap();
+        let repo = TestRepo { crate_dir: dir.path().join("work").join(crate_dir), dir, git_backend };
+
+        fs::create_dir_all(repo.home()).unwrap();
+        fs::create_dir_all(repo.crate_dir.join("src")).unwrap();
+        repo.write_stub_cargo("exit 0");
+
+        repo.git_in(repo.dir.path(), &["init", "--quiet", "--bare", "remote.git"]);
+        repo.git_in(&repo.work_dir(), &["init", "--quiet"]);
+
+        repo.write_file(".gitignore", "target\n");
+        repo.write_crate_file("Cargo.toml", "[package]\nname = \"foo\"\nversion = \"0.1.0\"\nauthors = [\"Tester\"]\n\
+                                             description = \"A test crate\"\nlicense = \"MIT\"\n\n[dependencies]\n");
+        repo.write_crate_file("Cargo.lock", "version = 3\n\n[[package]]\nname = \"foo\"\nversion = \"0.1.0\"\n");
+        repo.write_crate_file("CHANGELOG", "0.1.0\n-----\n* Initial release\n");
+        repo.write_crate_file("src/main.rs", "fn main() {}\n");
+
+        repo.git(&["add", "--all"]);
+        repo.git(&["commit", "--quiet", "--message", "Initial commit"]);
+        repo.git(&["tag", if crate_dir.is_empty() { "v0.1.0" } else { "foo-v0.1.0" }]);
+
+        let remote = repo.remote_dir();
+        repo.git(&["remote", "add", "origin", remote.to_str().unwrap()]);
+        repo.git(&["push", "--quiet", "--set-upstream", "origin", "HEAD"]);
+        repo.git(&["push", "--quiet", "--tags"]);
+        repo
+    }
+
+    /// Runs `rusty-release` with `args` in the directory of the cargo project.
+    pub fn run(&self, args: &[&str]) -> Output {
+        let path = format!("{}:{}", self.bin_dir().display(), env::var("PATH").unwrap_or_default());
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_rusty-release"));
+        cmd.args(args)
+           .current_dir(&self.crate_dir)
+           .env("PATH", path)
+           .env("EDITOR", "true")
+           .env_remove("VISUAL");
+
+        self.set_env(&mut cmd);
+        cmd.env("RUSTY_RELEASE_GIT_BACKEND", self.git_backend);
+        cmd.output().unwrap()
+    }
+
+    /// Like `run`, but panics if `rusty-release` fails.
+    pub fn release(&self, args: &[&str]) -> Output {
+        let output = self.run(args);
+        assert!(output.status.success(), "rusty-release {:?} failed:\n{}", args, stderr(&output));
+        output
+    }
+
+    /// Replaces the stub `cargo` by one running the shell `script`, after recording the
+    /// invocation - and for `cargo publish` the version of the published manifest -
+    /// and handling `cargo metadata`.
+    pub fn write_stub_cargo(&self, script: &str) {
+        let real_cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+        let cargo = self.bin_dir().join("cargo");
+        fs::create_dir_all(self.bin_dir()).unwrap();
+        fs::write(&cargo, format!("#!/bin/sh\n\
+                                   echo \"$@\" >> '{}'\n\
+                                   if [ \"$1\" = metadata ]; then exec '{}' \"$@\"; fi\n\
+                                   if [ \"$1\" = publish ]; then\n\
+                                   manifest=Cargo.toml\n\
+                                   for arg; do [ \"$prev\" = --manifest-path ] && manifest=$arg; prev=$arg; done\n\
+                                   grep -m 1 '^version' \"$manifest\" | cut -d '\"' -f 2 >> '{}'\n\
+                                   fi\n\
+                                   {}\n",
+                                  self.cargo_log().display(), real_cargo, self.publish_log().display(), script)).unwrap();
+
+        fs::set_permissions(&cargo, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    /// The invocations of the stub `cargo`, one line per invocation.
+    pub fn cargo_calls(&self) -> Vec<String> {
+        fs::read_to_string(self.cargo_log())
+            .map(|log| log.lines().map(str::to_string).collect())
+            .unwrap_or_default()
+    }
+
+    /// The versions published by the stub `cargo`.
+    pub fn published_versions(&self) -> Vec<String> {
+        fs::read_to_string(self.publish_log())
+            .map(|log| log.lines().map(str::to_string).collect())
+            .unwrap_or_default()
+    }
+
+    /// Runs git with `args` in the working repository and returns its output.
+    pub fn git(&self, args: &[&str]) -> String {
+        self.git_in(&self.crate_dir, args)
+    }
+
+    /// Runs git with `args` in the bare 'remote' repository and returns its output.
+    pub fn remote_git(&self, args: &[&str]) -> String {
+        self.git_in(&self.remote_dir(), args)
+    }
+
+    /// The tags of the working repository.
+    pub fn tags(&self) -> Vec<String> {
+        self.git(&["tag", "--list"]).lines().map(str::to_string).collect()
+    }
+
+    /// The tags of the 'remote' repository.
+    pub fn remote_tags(&self) -> Vec<String> {
+        self.remote_git(&["tag", "--list"]).lines().map(str::to_string).collect()
+    }
+
+    /// The subject lines of the commits in the working repository, the newest first.
+    pub fn commit_subjects(&self) -> Vec<String> {
+        self.git(&["log", "--format=%s"]).lines().map(str::to_string).collect()
+    }
+
+    /// The version in the `Cargo.toml` of the cargo project.
+    pub fn version(&self) -> String {
+        self.read_crate_file("Cargo.toml")
+            .lines()
+            .find(|l| l.starts_with("version"))
+            .and_then(|l| l.split('"').nth(1))
+            .unwrap()
+            .to_string()
+    }
+
+    /// Puts an `editor` running the shell `script` into the `PATH` and commits the
+    /// configuration `config` of the cargo project, which should use it by `editor = "editor"`.
+    pub fn with_stub_editor(&self, script: &str, config: &str) {
+        let editor = self.bin_dir().join("editor");
+        fs::create_dir_all(self.bin_dir()).unwrap();
+        fs::write(&editor, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        fs::set_permissions(&editor, fs::Permissions::from_mode(0o755)).unwrap();
+
+        self.write_crate_file(".rusty-release.toml", config);
+        self.git(&["add", ".rusty-release.toml"]);
+        self.git(&["commit", "--quiet", "--message", "Configure editor"]);
+    }
+
+    /// Writes `contents` to the file at `path` relative to the root directory of the git repository.
+    pub fn write_file(&self, path: &str, contents: &str) {
+        fs::write(self.work_dir().join(path), contents).unwrap();
+    }
+
+    /// Writes `contents` to the file at `path` relative to the directory of the cargo project.
+    pub fn write_crate_file(&self, path: &str, contents: &str) {
+        fs::write(self.crate_dir.join(path), contents).unwrap();
+    }
+
+    /// Reads the file at `path` relative to the directory of the cargo project.
+    pub fn read_crate_file(&self, path: &str) -> String {
+        fs::read_to_string(self.crate_dir.join(path)).unwrap()
+    }
+
+    /// Runs git with `args` in `dir`, panics if git fails.
+    fn git_in(&self, dir: &Path, args: &[&str]) -> String {
+        let mut cmd = Command::new("git");
+        cmd.args(args).current_dir(dir);
+        self.set_env(&mut cmd);
+
+        let output = cmd.output().unwrap();
+        assert!(output.status.success(), "git {:?} failed:\n{}", args, stderr(&output));
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    /// Isolates `cmd` from the configuration of the user.
+    fn set_env(&self, cmd: &mut Command) {
+        // cargo and rustup keep their directories, even if the home directory is replaced
+        if let Some(home) = env::var_os("HOME") {
+            let home = PathBuf::from(home);
+            cmd.env("CARGO_HOME", env::var_os("CARGO_HOME").unwrap_or_else(|| home.join(".cargo").into()))
+               .env("RUSTUP_HOME", env::var_os("RUSTUP_HOME").unwrap_or_else(|| home.join(".rustup").into()));
+        }
+
+        for (var, _) in env::vars_os() {
+            if var.to_string_lossy().starts_with("RUSTY_RELEASE_") {
+                cmd.env_remove(var);
+            }
+        }
+
+        cmd.env_remove("CARGO_TARGET_DIR");
+
+        cmd.env("HOME", self.home())
+           .env("XDG_CONFIG_HOME", self.home().join(".config"))
+           .env("GIT_CONFIG_NOSYSTEM", "1")
+           .env("GIT_AUTHOR_NAME", "Tester")
+           .env("GIT_AUTHOR_EMAIL", "tester@example.com")
+           .env("GIT_COMMITTER_NAME", "Tester")
+           .env("GIT_COMMITTER_EMAIL", "tester@example.com");
+    }
+
+    fn home(&self) -> PathBuf {
+        self.dir.path().join("home")
+    }
+
+    fn bin_dir(&self) -> PathBuf {
+        self.dir.path().join("bin")
+    }
+
+    fn cargo_log(&self) -> PathBuf {
+        self.dir.path().join("cargo.log")
+    }
+
+    fn publish_log(&self) -> PathBuf {
+        self.dir.path().join("publish.log")
+    }
+
+    fn work_dir(&self) -> PathBuf {
+        self.dir.path().join("work")
+    }
+
+    fn remote_dir(&self) -> PathBuf {
+        self.dir.path().join("remote.git")
+    }
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
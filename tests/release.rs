@@ -0,0 +1,490 @@
+extern crate tempfile;
+
+mod common;
+
+use common::{TestRepo, stdout, stderr};
+
+/// Runs each of the `tests` with every available git backend.
+macro_rules! git_backend_tests {
+    ($($test:ident),*) => {
+        mod cli {
+            $(
+                #[test]
+                fn $test() {
+                    super::$test("cli");
+                }
+            )*
+        }
+
+        #[cfg(feature = "libgit2")]
+        mod libgit2 {
+            $(
+                #[test]
+                fn $test() {
+                    super::$test("libgit2");
+                }
+            )*
+        }
+    }
+}
+
+git_backend_tests!(
+    patch_release,
+    minor_and_major_release,
+    no_push_and_no_publish,
+    settings_from_configuration_file,
+    development_version,
+    crate_in_sub_directory,
+    ignores_cargo_lock_outside_of_project,
+    offline_release_and_finish,
+    offline_development_version_and_finish,
+    fails_for_dirty_working_directory,
+    fails_for_existing_release_tag,
+    fails_for_failing_tests,
+    print_config,
+    commit_log_since_previous_release,
+    commit_log_of_first_release,
+    commit_log_ignores_releases_of_other_branches,
+    commit_log_grouped_by_type,
+    commit_log_grouped_by_path,
+    contributors_of_release
+);
+
+fn patch_release(git_backend: &'static str) {
+    let repo = TestRepo::new(git_backend);
+    repo.release(&["--yes", "patch"]);
+
+    assert_eq!(repo.version(), "0.1.1");
+    assert!(repo.read_crate_file("Cargo.lock").contains("version = \"0.1.1\""));
+    assert!(repo.read_crate_file("CHANGELOG").starts_with("0.1.1\n\n0.1.0\n"));
+    assert_eq!(repo.commit_subjects(), ["foo 0.1.1", "Initial commit"]);
+    assert_eq!(repo.tags(), ["v0.1.0", "v0.1.1"]);
+    assert_eq!(repo.git(&["status", "--porcelain"]), "");
+
+    assert_eq!(repo.remote_tags(), ["v0.1.0", "v0.1.1"]);
+    assert_eq!(repo.remote_git(&["rev-parse", "HEAD"]), repo.git(&["rev-parse", "HEAD"]));
+
+    let calls = repo.cargo_calls();
+    assert!(calls.iter().any(|c| c == "test"));
+    assert!(calls.iter().any(|c| c == "build --release"));
+    assert_eq!(calls.last().map(String::as_str), Some("publish"));
+}
+
+fn minor_and_major_release(git_backend: &'static str) {
+    let repo = TestRepo::new(git_backend);
+    repo.release(&["--yes", "minor"]);
+    assert_eq!(repo.version(), "0.2.0");
+
+    repo.release(&["--yes", "major"]);
+    assert_eq!(repo.version(), "1.0.0");
+    assert_eq!(repo.remote_tags(), ["v0.1.0", "v0.2.0", "v1.0.0"]);
+}
+
+fn no_push_and_no_publish(git_backend: &'static str) {
+    let repo = TestRepo::new(git_backend);
+    repo.release(&["--yes", "--no-git-push", "--no-cargo-publish", "patch"]);
+
+    assert_eq!(repo.tags(), ["v0.1.0", "v0.1.1"]);
+    assert_eq!(repo.remote_tags(), ["v0.1.0"]);
+    assert!(! repo.cargo_calls().iter().any(|c| c.starts_with("publish")));
+}
+
+fn settings_from_configuration_file(git_backend: &'static str) {
+    let repo = TestRepo::new(git_backend);
+    repo.write_crate_file(".rusty-release.toml",
+                          "cargo_publish = false\ncommit_message = \"Release <NEW_VERSION>\"\ntag_name = \"release-<NEW_VERSION>\"\n");
+
+    repo.git(&["add", ".rusty-release.toml"]);
+    repo.git(&["commit", "--quiet", "--message", "Add configuration"]);
+    repo.release(&["--yes", "patch"]);
+
+    assert_eq!(repo.commit_subjects()[0], "Release 0.1.1");
+    assert_eq!(repo.remote_tags(), ["release-0.1.1", "v0.1.0"]);
+    assert!(! repo.cargo_calls().iter().any(|c| c.starts_with("publish")));
+}
+
+fn development_version(git_backend: &'static str) {
+    let repo = TestRepo::new(git_backend);
+    repo.write_crate_file("Cargo.toml", &format!("{}\n[package.metadata.rusty-release]\ndev_version = \"minor\"\n",
+                                                 repo.read_crate_file("Cargo.toml")));
+
+    repo.git(&["commit", "--quiet", "--all", "--message", "Add development version"]);
+    repo.release(&["--yes", "patch"]);
+
+    assert_eq!(repo.version(), "0.2.0-dev");
+    assert_eq!(repo.published_versions(), ["0.1.1"]);
+    assert_eq!(repo.commit_subjects()[..2], ["Start foo 0.2.0-dev development", "foo 0.1.1"]);
+    assert_eq!(repo.remote_git(&["rev-parse", "HEAD"]), repo.git(&["rev-parse", "HEAD"]));
+    assert!(repo.git(&["show", "v0.1.1:Cargo.toml"]).contains("version = \"0.1.1\""));
+    assert!(repo.read_crate_file("CHANGELOG").starts_with("Unreleased\n\n0.1.1\n"));
+}
+
+fn crate_in_sub_directory(git_backend: &'static str) {
+    let repo = TestRepo::with_crate_dir(git_backend, "foo");
+    repo.release(&["--yes", "patch"]);
+
+    assert_eq!(repo.version(), "0.1.1");
+    assert_eq!(repo.remote_tags(), ["foo-v0.1.0", "foo-v0.1.1"]);
+}
+
+fn ignores_cargo_lock_outside_of_project(git_backend: &'static str) {
+    let repo = TestRepo::with_crate_dir(git_backend, "foo");
+    let unrelated_lock = "version = 3\n\n[[package]]\nname = \"foo\"\nversion = \"0.1.0\"\n";
+    repo.write_file("Cargo.lock", unrelated_lock);
+    repo.git(&["rm", "--quiet", "Cargo.lock"]);
+    repo.git(&["add", "../Cargo.lock"]);
+    repo.git(&["commit", "--quiet", "--message", "Move Cargo.lock"]);
+
+    repo.release(&["--yes", "patch"]);
+    assert_eq!(repo.version(), "0.1.1");
+    assert_eq!(repo.git(&["show", "HEAD:Cargo.lock"]), unrelated_lock);
+    assert!(! repo.cargo_calls().iter().any(|c| c.starts_with("metadata")));
+}
+
+fn offline_release_and_finish(git_backend: &'static str) {
+    let repo = TestRepo::new(git_backend);
+    repo.release(&["--yes", "--offline", "patch"]);
+
+    assert_eq!(repo.tags(), ["v0.1.0", "v0.1.1"]);
+    assert_eq!(repo.remote_tags(), ["v0.1.0"]);
+    assert!(! repo.cargo_calls().iter().any(|c| c.starts_with("publish")));
+
+    let output = repo.run(&["--yes", "patch"]);
+    assert!(! output.status.success());
+    assert!(stderr(&output).contains("pending release"));
+
+    repo.release(&["--finish"]);
+    assert_eq!(repo.remote_tags(), ["v0.1.0", "v0.1.1"]);
+    assert_eq!(repo.cargo_calls().last().map(String::as_str), Some("publish"));
+
+    let output = repo.run(&["--finish"]);
+    assert!(! output.status.success());
+}
+
+fn offline_development_version_and_finish(git_backend: &'static str) {
+    let repo = TestRepo::with_crate_dir(git_backend, "foo");
+    repo.write_crate_file("Cargo.toml", &format!("{}\n[package.metadata.rusty-release]\ndev_version = \"minor\"\n",
+                                                 repo.read_crate_file("Cargo.toml")));
+
+    repo.git(&["commit", "--quiet", "--all", "--message", "Add development version"]);
+    repo.release(&["--yes", "--offline", "patch"]);
+    assert_eq!(repo.version(), "0.2.0-dev");
+    assert!(repo.published_versions().is_empty());
+
+    repo.release(&["--finish"]);
+    assert_eq!(repo.published_versions(), ["0.1.1"]);
+    assert_eq!(repo.remote_tags(), ["foo-v0.1.0", "foo-v0.1.1"]);
+    assert_eq!(repo.remote_git(&["rev-parse", "HEAD"]), repo.git(&["rev-parse", "HEAD"]));
+    assert_eq!(repo.version(), "0.2.0-dev");
+    assert_eq!(repo.git(&["status", "--porcelain"]), "");
+    assert_eq!(repo.git(&["worktree", "list", "--porcelain"]).matches("worktree ").count(), 1);
+}
+
+fn fails_for_dirty_working_directory(git_backend: &'static str) {
+    let repo = TestRepo::new(git_backend);
+    repo.write_crate_file("src/main.rs", "fn main() { println!(); }\n");
+
+    let output = repo.run(&["--yes", "patch"]);
+    assert!(! output.status.success());
+    assert_eq!(repo.version(), "0.1.0");
+    assert_eq!(repo.tags(), ["v0.1.0"]);
+}
+
+fn fails_for_existing_release_tag(git_backend: &'static str) {
+    let repo = TestRepo::new(git_backend);
+    repo.git(&["tag", "v0.1.1"]);
+
+    let output = repo.run(&["--yes", "patch"]);
+    assert!(! output.status.success());
+    assert!(stderr(&output).contains("Tag 'v0.1.1' of the release already exists"));
+    assert_eq!(repo.version(), "0.1.0");
+}
+
+fn fails_for_failing_tests(git_backend: &'static str) {
+    let repo = TestRepo::new(git_backend);
+    repo.write_stub_cargo("if [ \"$1\" = test ]; then echo 'test failed' >&2; exit 101; fi");
+
+    let output = repo.run(&["--yes", "patch"]);
+    assert!(! output.status.success());
+    assert!(stderr(&output).contains("test failed"));
+    assert_eq!(repo.version(), "0.1.0");
+    assert_eq!(repo.commit_subjects(), ["Initial commit"]);
+}
+
+fn print_config(git_backend: &'static str) {
+    let repo = TestRepo::new(git_backend);
+    let output = repo.release(&["config"]);
+
+    assert!(stdout(&output).contains("tag_name = \"v<NEW_VERSION>\""));
+    assert_eq!(repo.tags(), ["v0.1.0"]);
+}
+
+/// Configures an editor inserting the commit log into the changelog section.
+fn insert_commit_log_into_changelog(repo: &TestRepo, settings: &str) {
+    repo.with_stub_editor("sed -i \"1r $2\" \"$1\"", &format!("editor = \"editor\"\n{}", settings));
+}
+
+fn commit_log_since_previous_release(git_backend: &'static str) {
+    let repo = TestRepo::new(git_backend);
+    repo.git(&["tag", "v0.1.0-rc.1", "HEAD"]);
+    insert_commit_log_into_changelog(&repo, "tag_name = \"release-<NEW_VERSION>\"\n");
+
+    repo.release(&["--yes", "patch"]);
+    let changelog = repo.read_crate_file("CHANGELOG");
+    assert!(changelog.starts_with("0.1.1\nCommits since the previous release v0.1.0:\n"));
+    assert!(changelog.contains("Configure editor"));
+    assert!(! changelog.contains("Initial commit"));
+}
+
+fn commit_log_of_first_release(git_backend: &'static str) {
+    let repo = TestRepo::new(git_backend);
+    repo.git(&["tag", "--delete", "v0.1.0"]);
+    repo.remote_git(&["tag", "--delete", "v0.1.0"]);
+    insert_commit_log_into_changelog(&repo, "");
+
+    repo.release(&["--yes", "current"]);
+    let changelog = repo.read_crate_file("CHANGELOG");
+    assert!(changelog.starts_with("0.1.0\nFirst release, all commits:\n"));
+    assert!(changelog.contains("Initial commit"));
+}
+
+fn commit_log_ignores_releases_of_other_branches(git_backend: &'static str) {
+    let repo = TestRepo::new(git_backend);
+    repo.git(&["checkout", "--quiet", "-b", "maintenance"]);
+    repo.write_crate_file("src/lib.rs", "pub fn fix() {}\n");
+    repo.git(&["add", "src/lib.rs"]);
+    repo.git(&["commit", "--quiet", "--message", "Fix on maintenance branch"]);
+    repo.git(&["tag", "v0.1.1"]);
+    repo.git(&["checkout", "--quiet", "-"]);
+    insert_commit_log_into_changelog(&repo, "");
+
+    repo.release(&["--yes", "minor"]);
+    let changelog = repo.read_crate_file("CHANGELOG");
+    assert!(changelog.starts_with("0.2.0\nCommits since the previous release v0.1.0:\n"), "{}", changelog);
+    assert!(changelog.contains("Configure editor"));
+    assert!(! changelog.contains("Fix on maintenance branch"));
+}
+
+/// Commits a conventional feature and fix and configures the commit log by `log_settings`.
+fn commit_feature_and_fix(repo: &TestRepo, log_settings: &str) {
+    insert_commit_log_into_changelog(repo, &format!("log_format = \"<SUBJECT> <REFS>\"\n{}", log_settings));
+
+    repo.write_crate_file("src/lib.rs", "pub fn foo() {}\n");
+    repo.git(&["add", "src/lib.rs"]);
+    repo.git(&["commit", "--quiet", "--message", "feat: add foo"]);
+
+    repo.write_crate_file("README.md", "Foo\n");
+    repo.git(&["add", "README.md"]);
+    repo.git(&["commit", "--quiet", "--message", "fix(docs): describe foo (#3)"]);
+}
+
+fn commit_log_grouped_by_type(git_backend: &'static str) {
+    let repo = TestRepo::new(git_backend);
+    commit_feature_and_fix(&repo, "log_group = \"type\"\n");
+
+    repo.release(&["--yes", "patch"]);
+    assert!(repo.read_crate_file("CHANGELOG").starts_with("0.1.1\nCommits since the previous release v0.1.0:\n\n\
+                                                           Features:\n  add foo\n\n\
+                                                           Bug Fixes:\n  docs: describe foo #3\n\n\
+                                                           Other:\n  Configure editor\n"));
+}
+
+fn commit_log_grouped_by_path(git_backend: &'static str) {
+    let repo = TestRepo::with_crate_dir(git_backend, "crates/foo");
+    commit_feature_and_fix(&repo, "log_group = \"path\"\n");
+
+    repo.release(&["--yes", "patch"]);
+    assert!(repo.read_crate_file("CHANGELOG").starts_with("0.1.1\nCommits since the previous release foo-v0.1.0:\n\n\
+                                                           .rusty-release.toml:\n  Configure editor\n\n\
+                                                           README.md:\n  fix(docs): describe foo #3\n\n\
+                                                           src/:\n  feat: add foo\n"));
+}
+
+fn contributors_of_release(git_backend: &'static str) {
+    let repo = TestRepo::new(git_backend);
+    repo.with_stub_editor("sed -i '1a Thanks to <CONTRIBUTORS>' \"$1\"", "editor = \"editor\"\n");
+    repo.write_crate_file(".mailmap", "Bob <bob@example.com> <bob@old.example.com>\n");
+    repo.git(&["add", ".mailmap"]);
+    repo.git(&["commit", "--quiet", "--message", "Add mailmap"]);
+
+    for (author, file) in &[("bob <bob@old.example.com>", "foo.rs"), ("Bob <bob@example.com>", "bar.rs")] {
+        repo.write_crate_file(file, "\n");
+        repo.git(&["add", file]);
+        repo.git(&["commit", "--quiet", "--author", author, "--message", "Add file"]);
+    }
+
+    repo.release(&["--yes", "patch"]);
+    assert!(repo.read_crate_file("CHANGELOG").starts_with("0.1.1\nThanks to Bob (first contribution), Tester\n"));
+    assert!(repo.git(&["tag", "--list", "--format=%(contents)", "v0.1.1"]).contains("Thanks to Bob (first contribution), Tester"));
+}
+
+#[cfg(not(feature = "libgit2"))]
+#[test]
+fn libgit2_backend_needs_feature() {
+    let repo = TestRepo::new("libgit2");
+    let output = repo.run(&["--yes", "patch"]);
+
+    assert!(! output.status.success());
+    assert!(stderr(&output).contains("cargo feature 'libgit2'"));
+    assert_eq!(repo.version(), "0.1.0");
+}
+
+#[test]
+fn crate_settings_override_shared_settings() {
+    let repo = TestRepo::with_crate_dir("cli", "foo");
+    repo.write_file(".rusty-release.toml", "tag_name = \"shared-<NEW_VERSION>\"\ncommit_message = \"Shared <NEW_VERSION>\"\n");
+    repo.write_crate_file("Cargo.toml", &format!("{}\n[package.metadata.rusty-release]\ntag_name = \"crate-<NEW_VERSION>\"\n",
+                                                 repo.read_crate_file("Cargo.toml")));
+
+    repo.git(&["add", "--all"]);
+    repo.git(&["commit", "--quiet", "--message", "Configure tag names"]);
+    repo.release(&["--yes", "patch"]);
+
+    assert_eq!(repo.tags(), ["crate-0.1.1", "foo-v0.1.0"]);
+    assert_eq!(repo.commit_subjects()[0], "Shared 0.1.1");
+}
+
+#[test]
+fn verbose_release_logs_commands() {
+    let repo = TestRepo::new("cli");
+    let output = repo.release(&["--yes", "--verbose", "patch"]);
+
+    let log = stderr(&output);
+    assert!(log.lines().any(|l| l.starts_with("$ git tag --annotate ") && l.contains("'--message=foo 0.1.1")), "{}", log);
+    assert!(log.lines().any(|l| l.starts_with("$ cargo publish (exit code 0, ")), "{}", log);
+}
+
+#[test]
+fn dry_run_changes_nothing() {
+    let repo = TestRepo::new("cli");
+    let head = repo.git(&["rev-parse", "HEAD"]);
+    let output = repo.release(&["--dry-run", "patch"]);
+
+    let out = stdout(&output);
+    assert!(out.contains("Version:             0.1.0 -> 0.1.1"), "{}", out);
+    assert!(out.lines().any(|l| l.starts_with("$ cargo test (exit code 0, ")), "{}", out);
+    assert!(out.lines().any(|l| l.starts_with("$ git diff-files ")), "{}", out);
+
+    assert_eq!(repo.version(), "0.1.0");
+    assert_eq!(repo.tags(), ["v0.1.0"]);
+    assert_eq!(repo.git(&["rev-parse", "HEAD"]), head);
+    assert_eq!(repo.git(&["status", "--porcelain"]), "");
+    assert_eq!(repo.cargo_calls(), ["test"]);
+}
+
+#[test]
+fn fails_for_signing_with_libgit2() {
+    let repo = TestRepo::new("libgit2");
+    repo.write_crate_file(".rusty-release.toml", "sign_tag = true\n");
+    repo.git(&["add", ".rusty-release.toml"]);
+    repo.git(&["commit", "--quiet", "--message", "Sign tags"]);
+
+    let output = repo.run(&["--yes", "patch"]);
+    assert!(! output.status.success());
+    assert!(stderr(&output).contains("Invalid configuration file"));
+    assert!(stderr(&output).contains("'sign_tag' isn't supported by the git backend 'libgit2'"));
+    assert_eq!(repo.version(), "0.1.0");
+    assert_eq!(repo.git(&["status", "--porcelain"]), "");
+}
+
+#[test]
+fn prefers_markdown_changelog() {
+    let repo = TestRepo::new("cli");
+    repo.write_crate_file("CHANGELOG.md", "0.1.0\n-----\n* Initial release\n");
+    repo.git(&["add", "CHANGELOG.md"]);
+    repo.git(&["commit", "--quiet", "--message", "Add markdown changelog"]);
+
+    let output = repo.release(&["--yes", "patch"]);
+    assert!(stderr(&output).contains("Found several changelogs"));
+    assert!(repo.read_crate_file("CHANGELOG.md").starts_with("0.1.1\n"));
+    assert!(repo.read_crate_file("CHANGELOG").starts_with("0.1.0\n"));
+}
+
+#[test]
+fn configured_changelog() {
+    let repo = TestRepo::new("cli");
+    repo.git(&["mv", "CHANGELOG", "RELEASES.txt"]);
+    repo.write_crate_file(".rusty-release.toml", "changelog = \"RELEASES.txt\"\n");
+    repo.git(&["add", ".rusty-release.toml"]);
+    repo.git(&["commit", "--quiet", "--message", "Rename changelog"]);
+
+    repo.release(&["--yes", "patch"]);
+    assert!(repo.read_crate_file("RELEASES.txt").starts_with("0.1.1\n"));
+}
+
+#[test]
+fn fails_for_missing_required_changelog() {
+    let repo = TestRepo::new("cli");
+    repo.git(&["rm", "--quiet", "CHANGELOG"]);
+    repo.git(&["commit", "--quiet", "--message", "Remove changelog"]);
+
+    repo.write_crate_file(".rusty-release.toml", "changelog_required = true\n");
+    repo.git(&["add", ".rusty-release.toml"]);
+    repo.git(&["commit", "--quiet", "--message", "Require changelog"]);
+
+    let output = repo.run(&["--yes", "patch"]);
+    assert!(! output.status.success());
+    assert!(stderr(&output).contains("'changelog_required' is set"));
+    assert_eq!(repo.version(), "0.1.0");
+}
+
+#[test]
+fn warns_for_empty_changelog_section() {
+    let repo = TestRepo::new("cli");
+    let output = repo.release(&["--yes", "patch"]);
+    assert!(stderr(&output).contains("The changelog section of 0.1.1 is empty!"));
+}
+
+#[test]
+fn changelog_section_written_by_editor() {
+    let repo = TestRepo::new("cli");
+    repo.with_stub_editor("sed -i '1a * Fix the frobnicator' \"$1\"", "editor = \"editor\"\n");
+
+    let output = repo.release(&["--yes", "patch"]);
+    assert!(! stderr(&output).contains("is empty"));
+    assert!(repo.read_crate_file("CHANGELOG").starts_with("0.1.1\n* Fix the frobnicator\n"));
+}
+
+#[test]
+fn release_notes() {
+    let repo = TestRepo::new("cli");
+    repo.with_stub_editor("sed -i '1a ### Fixed\\n* Fix the frobnicator' \"$1\"", "editor = \"editor\"\n");
+
+    repo.release(&["--yes", "patch"]);
+    assert_eq!(repo.git(&["tag", "--list", "--format=%(contents)", "v0.1.1"]), "foo 0.1.1\n\n### Fixed\n* Fix the frobnicator\n\n");
+    assert_eq!(repo.read_crate_file("target/release-notes.md"), "### Fixed\n* Fix the frobnicator\n");
+
+    assert_eq!(stdout(&repo.release(&["notes"])), "### Fixed\n* Fix the frobnicator\n");
+    assert_eq!(stdout(&repo.release(&["notes", "v0.1.0"])), "* Initial release\n");
+
+    let output = repo.run(&["notes", "0.2.0"]);
+    assert!(! output.status.success());
+    assert!(stderr(&output).contains("Couldn't find the release notes of 0.2.0"));
+}
+
+#[test]
+fn release_notes_from_keep_a_changelog() {
+    let repo = TestRepo::new("cli");
+    repo.git(&["rm", "--quiet", "CHANGELOG"]);
+    repo.write_crate_file("CHANGELOG.md", "# Changelog\n\n## [Unreleased]\n\n## [0.1.0] - 2019-01-01\n* Initial release\n\n\
+                                           [0.1.0]: https://example.com/foo/releases/v0.1.0\n");
+    repo.git(&["add", "CHANGELOG.md"]);
+    repo.git(&["commit", "--quiet", "--message", "Keep a changelog"]);
+    repo.with_stub_editor("sed -i '/^## \\[0.1.1\\]/a ### Fixed\\n* Fix the frobnicator' \"$1\"", "editor = \"editor\"\n");
+
+    repo.release(&["--yes", "patch"]);
+    let changelog = repo.read_crate_file("CHANGELOG.md");
+    assert!(changelog.starts_with("# Changelog\n\n## [0.1.1] - "), "{}", changelog);
+    assert!(changelog.contains("\n### Fixed\n* Fix the frobnicator\n\n## [0.1.0] - 2019-01-01\n"), "{}", changelog);
+    assert_eq!(repo.git(&["tag", "--list", "--format=%(contents)", "v0.1.1"]), "foo 0.1.1\n\n### Fixed\n* Fix the frobnicator\n\n");
+}
+
+#[test]
+fn editor_command_with_placeholders_and_without_commit_log() {
+    let repo = TestRepo::new("cli");
+    repo.with_stub_editor("sed -i \"1a * $1, $# arguments\" \"$2\"",
+                          "editor = \"editor 'two words' {changelog} {log}\"\ncommit_log = false\n");
+
+    repo.release(&["--yes", "patch"]);
+    assert!(repo.read_crate_file("CHANGELOG").starts_with("0.1.1\n* two words, 2 arguments\n"));
+}